@@ -0,0 +1,173 @@
+use std::{
+    collections::{HashMap, hash_map::Entry},
+    ffi::{CStr, c_char, c_int},
+};
+
+use bevy_ecs::{
+    component::Component, entity::Entity, event::Event, name::Name, resource::Resource,
+    world::World,
+};
+
+/// A keyboard reported by SDL. Spawned with a [`Name`] when the keyboard is connected and
+/// despawned when it is removed.
+///
+/// Keyboard input from this keyboard is sent as a [`SdlKeyboardInput`](crate::SdlKeyboardInput)
+/// with this entity as its device.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SdlKeyboard {
+    /// The SDL id of the keyboard.
+    pub id: u32,
+}
+
+/// A mouse reported by SDL. Spawned with a [`Name`] when the mouse is connected and despawned when
+/// it is removed.
+///
+/// Mouse input from this mouse is sent as [`SdlMouseMotion`](crate::SdlMouseMotion),
+/// [`SdlMouseButtonInput`](crate::SdlMouseButtonInput) and
+/// [`SdlMouseWheel`](crate::SdlMouseWheel) with this entity as its device.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SdlMouse {
+    /// The SDL id of the mouse.
+    pub id: u32,
+}
+
+/// The entities of the connected keyboards, mice, gamepads, joysticks, haptic devices and sensors,
+/// keyed by their SDL id.
+///
+/// Input that isn't from a physical device, like touch input emulating a mouse or keys sent by
+/// an on-screen keyboard, has no entity.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct InputDevices {
+    pub keyboards: HashMap<u32, Entity>,
+    pub mice: HashMap<u32, Entity>,
+    /// Keyed by the SDL joystick id, see [`SdlGamepad`](crate::SdlGamepad).
+    pub gamepads: HashMap<u32, Entity>,
+    /// Keyed by the SDL joystick id, see [`SdlJoystick`](crate::SdlJoystick).
+    pub joysticks: HashMap<u32, Entity>,
+    /// Keyed by the SDL haptic id, see [`SdlHaptic`](crate::SdlHaptic).
+    pub haptics: HashMap<u32, Entity>,
+    /// Keyed by the SDL sensor id, see [`SdlSensor`](crate::SdlSensor).
+    pub sensors: HashMap<u32, Entity>,
+}
+
+impl InputDevices {
+    pub fn keyboard(&self, id: u32) -> Option<Entity> {
+        self.keyboards.get(&id).copied()
+    }
+
+    pub fn mouse(&self, id: u32) -> Option<Entity> {
+        self.mice.get(&id).copied()
+    }
+
+    pub fn gamepad(&self, id: u32) -> Option<Entity> {
+        self.gamepads.get(&id).copied()
+    }
+
+    pub fn joystick(&self, id: u32) -> Option<Entity> {
+        self.joysticks.get(&id).copied()
+    }
+
+    pub fn haptic(&self, id: u32) -> Option<Entity> {
+        self.haptics.get(&id).copied()
+    }
+
+    pub fn sensor(&self, id: u32) -> Option<Entity> {
+        self.sensors.get(&id).copied()
+    }
+}
+
+/// Sent when a keyboard, mouse, joystick, haptic device or sensor is connected or removed. Removed
+/// devices are despawned right after the event is sent.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputDeviceEvent {
+    KeyboardAdded(Entity),
+    KeyboardRemoved(Entity),
+    MouseAdded(Entity),
+    MouseRemoved(Entity),
+    JoystickAdded(Entity),
+    JoystickRemoved(Entity),
+    HapticAdded(Entity),
+    HapticRemoved(Entity),
+    /// Sensors are only added at startup, see [`SdlSensor`](crate::SdlSensor).
+    SensorAdded(Entity),
+}
+
+/// Spawns and despawns device entities to match the keyboards and mice SDL knows about.
+///
+/// SDL reports the device id of added and removed devices, but `sdl3` doesn't pass it through, so
+/// the devices are compared against SDL's list of connected devices instead.
+pub fn sync_input_devices(world: &mut World) {
+    // SAFETY: only called on the main thread, the lists are freed by `sdl_id_list`
+    let keyboards = unsafe { sdl_id_list(sdl3::sys::keyboard::SDL_GetKeyboards) };
+    let mice = unsafe { sdl_id_list(sdl3::sys::mouse::SDL_GetMice) };
+
+    let mut devices = world.resource::<InputDevices>().clone();
+
+    for (id, entity) in devices.keyboards.clone() {
+        if !keyboards.contains(&id) {
+            devices.keyboards.remove(&id);
+            world.send_event(InputDeviceEvent::KeyboardRemoved(entity));
+            world.despawn(entity);
+        }
+    }
+    for id in keyboards {
+        if let Entry::Vacant(entry) = devices.keyboards.entry(id) {
+            // SAFETY: returns a string owned by SDL or null
+            let name = unsafe { sdl_name(sdl3::sys::keyboard::SDL_GetKeyboardNameForID(id)) };
+            let entity = world.spawn((SdlKeyboard { id }, Name::new(name))).id();
+            entry.insert(entity);
+            world.send_event(InputDeviceEvent::KeyboardAdded(entity));
+        }
+    }
+
+    for (id, entity) in devices.mice.clone() {
+        if !mice.contains(&id) {
+            devices.mice.remove(&id);
+            world.send_event(InputDeviceEvent::MouseRemoved(entity));
+            world.despawn(entity);
+        }
+    }
+    for id in mice {
+        if let Entry::Vacant(entry) = devices.mice.entry(id) {
+            // SAFETY: returns a string owned by SDL or null
+            let name = unsafe { sdl_name(sdl3::sys::mouse::SDL_GetMouseNameForID(id)) };
+            let entity = world.spawn((SdlMouse { id }, Name::new(name))).id();
+            entry.insert(entity);
+            world.send_event(InputDeviceEvent::MouseAdded(entity));
+        }
+    }
+
+    world.insert_resource(devices);
+}
+
+/// Calls one of the SDL functions that return a list of device ids and copies the ids out.
+///
+/// # Safety
+///
+/// `get` must return null or a list of `count` ids that needs to be freed with `SDL_free`.
+pub unsafe fn sdl_id_list(get: unsafe extern "C" fn(*mut c_int) -> *mut u32) -> Vec<u32> {
+    let mut count = 0;
+    unsafe {
+        let ids = get(&mut count);
+        if ids.is_null() {
+            return Vec::new();
+        }
+        let list = std::slice::from_raw_parts(ids, count as usize).to_vec();
+        sdl3::sys::stdinc::SDL_free(ids.cast());
+        list
+    }
+}
+
+/// Copies a string returned by SDL. Null is returned as an empty string.
+///
+/// # Safety
+///
+/// `name` must be null or point to a valid C string.
+pub unsafe fn sdl_name(name: *const c_char) -> String {
+    if name.is_null() {
+        return String::new();
+    }
+    unsafe { CStr::from_ptr(name) }
+        .to_string_lossy()
+        .into_owned()
+}
@@ -0,0 +1,781 @@
+use std::{collections::HashMap, time::Duration};
+
+use bevy_ecs::{
+    component::Component,
+    entity::Entity,
+    event::{Event, EventReader},
+    query::Changed,
+    system::{Local, Query},
+    world::World,
+};
+use bevy_input::gamepad::{
+    GamepadAxis, GamepadButton, GamepadConnection, GamepadConnectionEvent, GamepadRumbleIntensity,
+    GamepadRumbleRequest, RawGamepadAxisChangedEvent, RawGamepadButtonChangedEvent,
+    RawGamepadEvent,
+};
+use bevy_input::touch::TouchPhase;
+use bevy_math::{Vec2, Vec3};
+use sdl3::{
+    gamepad::{Axis as SdlAxis, Button as SdlButton, Gamepad, GamepadType},
+    joystick::ConnectionState,
+    sys::sensor::SDL_SensorType,
+};
+
+use crate::{
+    SDL_CONTEXT,
+    devices::InputDevices,
+    joystick::handle_joystick_removed,
+    non_send_marker::NonSendMarker,
+    power::Battery,
+    timestamp::{LastInputTimestamps, sdl_ticks_ns, send_timestamped},
+};
+
+/// A gamepad opened through SDL. Added to the entity of the bevy
+/// [`Gamepad`](bevy_input::gamepad::Gamepad) and removed when the gamepad is disconnected.
+///
+/// Like other bevy backends, the entity itself is kept after the gamepad is disconnected. A
+/// gamepad that is connected again gets a new entity, since SDL assigns it a new id.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SdlGamepad {
+    /// The SDL joystick id of the gamepad.
+    pub id: u32,
+}
+
+/// What SDL knows about a gamepad. Added when the gamepad is connected.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct GamepadInfo {
+    pub name: String,
+    pub vendor_id: Option<u16>,
+    pub product_id: Option<u16>,
+    pub product_version: Option<u16>,
+    /// The type of the gamepad, e.g. to show the right button glyphs.
+    pub gamepad_type: GamepadType,
+    pub serial_number: Option<String>,
+    pub connection_state: GamepadConnectionState,
+}
+
+/// How a gamepad is connected to the system.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum GamepadConnectionState {
+    #[default]
+    Unknown,
+    Wired,
+    Wireless,
+}
+
+impl From<ConnectionState> for GamepadConnectionState {
+    fn from(state: ConnectionState) -> Self {
+        match state {
+            ConnectionState::Wired => GamepadConnectionState::Wired,
+            ConnectionState::Wireless => GamepadConnectionState::Wireless,
+            ConnectionState::Invalid | ConnectionState::Unknown => GamepadConnectionState::Unknown,
+        }
+    }
+}
+
+impl GamepadInfo {
+    fn new(gamepad: &Gamepad) -> Self {
+        Self {
+            name: gamepad.name().unwrap_or_default(),
+            vendor_id: gamepad.vendor_id(),
+            product_id: gamepad.product_id(),
+            product_version: gamepad.product_version(),
+            gamepad_type: gamepad.r#type(),
+            serial_number: gamepad.serial_number(),
+            connection_state: gamepad
+                .connection_state()
+                .map(GamepadConnectionState::from)
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// The player index of a gamepad, which some gamepads show with their LEDs. Change it to set the
+/// player index of the gamepad.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GamepadPlayerIndex(pub Option<u16>);
+
+/// The color of the LED of a gamepad. Insert or change it to set the color of the LED, e.g. of a
+/// DualShock 4 or DualSense light bar.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct GamepadLed {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+pub fn handle_gamepad_added(world: &mut World, timestamp: u64, which: u32) {
+    if world.resource::<InputDevices>().gamepad(which).is_some() {
+        return;
+    }
+    let Some((info, player_index)) = SDL_CONTEXT.with_borrow_mut(|context| {
+        let context = context.as_mut()?;
+        let gamepad = context.gamepad.open(which).ok()?;
+        let info = GamepadInfo::new(&gamepad);
+        let player_index = GamepadPlayerIndex(gamepad.player_index());
+        context.gamepads.insert(which, gamepad);
+        Some((info, player_index))
+    }) else {
+        return;
+    };
+    // a joystick turns into a gamepad when a mapping is added for it
+    handle_joystick_removed(world, which);
+
+    let connection = GamepadConnection::Connected {
+        name: info.name.clone(),
+        vendor_id: info.vendor_id,
+        product_id: info.product_id,
+    };
+    let entity = world
+        .spawn((
+            SdlGamepad { id: which },
+            info,
+            player_index,
+            GamepadSensors::new(which),
+            Battery::for_joystick(which),
+        ))
+        .id();
+    world
+        .resource_mut::<InputDevices>()
+        .gamepads
+        .insert(which, entity);
+
+    let event = GamepadConnectionEvent::new(entity, connection);
+    send_timestamped(world, timestamp, RawGamepadEvent::from(event.clone()));
+    world.send_event(event);
+}
+
+pub fn handle_gamepad_removed(world: &mut World, timestamp: u64, which: u32) {
+    SDL_CONTEXT.with_borrow_mut(|context| {
+        if let Some(context) = context.as_mut() {
+            context.gamepads.remove(&which);
+        }
+    });
+    let Some(entity) = world.resource_mut::<InputDevices>().gamepads.remove(&which) else {
+        return;
+    };
+    if let Ok(mut entity_mut) = world.get_entity_mut(entity) {
+        entity_mut.remove::<(
+            SdlGamepad,
+            GamepadInfo,
+            GamepadPlayerIndex,
+            GamepadLed,
+            GamepadSensors,
+            Battery,
+        )>();
+    }
+
+    let event = GamepadConnectionEvent::new(entity, GamepadConnection::Disconnected);
+    send_timestamped(world, timestamp, RawGamepadEvent::from(event.clone()));
+    world.send_event(event);
+}
+
+pub fn handle_gamepad_button(
+    world: &mut World,
+    timestamp: u64,
+    which: u32,
+    button: SdlButton,
+    pressed: bool,
+) {
+    let Some(gamepad) = world.resource::<InputDevices>().gamepad(which) else {
+        return;
+    };
+    let value = if pressed { 1.0 } else { 0.0 };
+    send_button(
+        world,
+        timestamp,
+        which,
+        RawGamepadButtonChangedEvent::new(gamepad, convert_sdl_button(button), value),
+    );
+}
+
+pub fn handle_gamepad_axis(
+    world: &mut World,
+    timestamp: u64,
+    which: u32,
+    axis: SdlAxis,
+    value: i16,
+) {
+    let Some(gamepad) = world.resource::<InputDevices>().gamepad(which) else {
+        return;
+    };
+    // bevy uses the full range of an i16 for both directions
+    let value = (f32::from(value) / f32::from(i16::MAX)).max(-1.0);
+
+    // bevy treats the triggers as analog buttons, and stick axes as pointing up rather than down
+    let axis = match axis {
+        SdlAxis::TriggerLeft => {
+            let event =
+                RawGamepadButtonChangedEvent::new(gamepad, GamepadButton::LeftTrigger2, value);
+            send_button(world, timestamp, which, event);
+            return;
+        }
+        SdlAxis::TriggerRight => {
+            let event =
+                RawGamepadButtonChangedEvent::new(gamepad, GamepadButton::RightTrigger2, value);
+            send_button(world, timestamp, which, event);
+            return;
+        }
+        SdlAxis::LeftX => RawGamepadAxisChangedEvent::new(gamepad, GamepadAxis::LeftStickX, value),
+        SdlAxis::LeftY => RawGamepadAxisChangedEvent::new(gamepad, GamepadAxis::LeftStickY, -value),
+        SdlAxis::RightX => {
+            RawGamepadAxisChangedEvent::new(gamepad, GamepadAxis::RightStickX, value)
+        }
+        SdlAxis::RightY => {
+            RawGamepadAxisChangedEvent::new(gamepad, GamepadAxis::RightStickY, -value)
+        }
+    };
+
+    world
+        .resource_mut::<LastInputTimestamps>()
+        .gamepads
+        .insert(which, timestamp);
+    send_timestamped(world, timestamp, RawGamepadEvent::from(axis));
+    world.send_event(axis);
+}
+
+fn send_button(world: &mut World, timestamp: u64, which: u32, event: RawGamepadButtonChangedEvent) {
+    world
+        .resource_mut::<LastInputTimestamps>()
+        .gamepads
+        .insert(which, timestamp);
+    send_timestamped(world, timestamp, RawGamepadEvent::from(event));
+    world.send_event(event);
+}
+
+/// The motion sensors of a gamepad. Readings are sent as [`GamepadSensorEvent`]s while a sensor is
+/// enabled.
+///
+/// Sensors are disabled by default, since they send a lot of events. Change `accelerometer` or
+/// `gyroscope` to enable or disable them.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GamepadSensors {
+    pub accelerometer: bool,
+    pub gyroscope: bool,
+    has_accelerometer: bool,
+    has_gyroscope: bool,
+}
+
+impl GamepadSensors {
+    fn new(id: u32) -> Self {
+        // SAFETY: only called on the main thread after the gamepad was opened
+        let (has_accelerometer, has_gyroscope) = unsafe {
+            let gamepad = sdl3::sys::gamepad::SDL_GetGamepadFromID(id);
+            (
+                sdl3::sys::gamepad::SDL_GamepadHasSensor(gamepad, SDL_SensorType::ACCEL),
+                sdl3::sys::gamepad::SDL_GamepadHasSensor(gamepad, SDL_SensorType::GYRO),
+            )
+        };
+        Self {
+            accelerometer: false,
+            gyroscope: false,
+            has_accelerometer,
+            has_gyroscope,
+        }
+    }
+
+    pub fn has_accelerometer(&self) -> bool {
+        self.has_accelerometer
+    }
+
+    pub fn has_gyroscope(&self) -> bool {
+        self.has_gyroscope
+    }
+}
+
+/// system to apply changes to [`GamepadPlayerIndex`], [`GamepadLed`] and [`GamepadSensors`]
+pub fn update_gamepads(
+    player_indices: Query<(&SdlGamepad, &GamepadPlayerIndex), Changed<GamepadPlayerIndex>>,
+    leds: Query<(&SdlGamepad, &GamepadLed), Changed<GamepadLed>>,
+    sensors: Query<(&SdlGamepad, &GamepadSensors), Changed<GamepadSensors>>,
+    // gamepads need to be updated on the main thread
+    _non_send: NonSendMarker,
+) {
+    SDL_CONTEXT.with_borrow_mut(|context| {
+        let Some(context) = context.as_mut() else {
+            return;
+        };
+
+        for (SdlGamepad { id }, GamepadPlayerIndex(player_index)) in &player_indices {
+            let Some(gamepad) = context.gamepads.get(id) else {
+                continue;
+            };
+            if gamepad.player_index() != *player_index {
+                let _ = match player_index {
+                    Some(player_index) => gamepad.set_player_index(*player_index),
+                    None => gamepad.unset_player_index(),
+                };
+            }
+        }
+
+        for (SdlGamepad { id }, led) in &leds {
+            if let Some(gamepad) = context.gamepads.get_mut(id) {
+                let _ = gamepad.set_led(led.red, led.green, led.blue);
+            }
+        }
+    });
+
+    for (SdlGamepad { id }, sensors) in &sensors {
+        // SAFETY: only called on the main thread, SDL checks if the gamepad is still open
+        unsafe {
+            let gamepad = sdl3::sys::gamepad::SDL_GetGamepadFromID(*id);
+            for (sensor, enabled) in [
+                (SDL_SensorType::ACCEL, sensors.accelerometer),
+                (SDL_SensorType::GYRO, sensors.gyroscope),
+            ] {
+                if sdl3::sys::gamepad::SDL_GamepadSensorEnabled(gamepad, sensor) != enabled {
+                    sdl3::sys::gamepad::SDL_SetGamepadSensorEnabled(gamepad, sensor, enabled);
+                }
+            }
+        }
+    }
+}
+
+/// A motion sensor of a gamepad.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum GamepadSensorType {
+    /// Acceleration in m/s², including gravity.
+    Accelerometer,
+    /// Angular velocity in rad/s.
+    Gyroscope,
+}
+
+/// A reading of an enabled gamepad sensor, see [`GamepadSensors`].
+///
+/// The axes are relative to the gamepad held level with the face buttons up: X points to the right,
+/// Y points up and Z points toward the player.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct GamepadSensorEvent {
+    pub gamepad: Entity,
+    pub sensor: GamepadSensorType,
+    pub data: Vec3,
+    /// The SDL timestamp of the event in nanoseconds.
+    pub timestamp: u64,
+    /// The time the sensor took the reading in nanoseconds, if the gamepad reports it. Uses the
+    /// clock of the gamepad, so it can only be compared to other readings of the same gamepad.
+    pub sensor_timestamp: u64,
+}
+
+pub fn handle_gamepad_sensor(
+    world: &mut World,
+    timestamp: u64,
+    which: u32,
+    sensor: i32,
+    data: [f32; 3],
+    sensor_timestamp: u64,
+) {
+    let Some(gamepad) = world.resource::<InputDevices>().gamepad(which) else {
+        return;
+    };
+    let sensor = match SDL_SensorType(sensor) {
+        SDL_SensorType::ACCEL => GamepadSensorType::Accelerometer,
+        SDL_SensorType::GYRO => GamepadSensorType::Gyroscope,
+        _ => return,
+    };
+
+    world.send_event(GamepadSensorEvent {
+        gamepad,
+        sensor,
+        data: Vec3::from_array(data),
+        timestamp,
+        sensor_timestamp,
+    });
+}
+
+/// A finger touching, moving on or leaving the touchpad of a gamepad, like the touchpad of a
+/// DualShock 4 or DualSense.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct GamepadTouchpadEvent {
+    pub gamepad: Entity,
+    /// The index of the touchpad, most gamepads only have one.
+    pub touchpad: u32,
+    /// The index of the finger on the touchpad.
+    pub finger: u32,
+    pub phase: TouchPhase,
+    /// The normalized position of the finger, from (0, 0) in the top left to (1, 1) in the bottom
+    /// right.
+    pub position: Vec2,
+    /// The normalized pressure of the finger, from 0 to 1.
+    pub pressure: f32,
+    /// The SDL timestamp of the event in nanoseconds.
+    pub timestamp: u64,
+}
+
+#[allow(clippy::too_many_arguments)]
+pub fn handle_gamepad_touchpad(
+    world: &mut World,
+    timestamp: u64,
+    which: u32,
+    touchpad: i32,
+    finger: i32,
+    phase: TouchPhase,
+    x: f32,
+    y: f32,
+    pressure: f32,
+) {
+    let Some(gamepad) = world.resource::<InputDevices>().gamepad(which) else {
+        return;
+    };
+    world
+        .resource_mut::<LastInputTimestamps>()
+        .gamepads
+        .insert(which, timestamp);
+
+    world.send_event(GamepadTouchpadEvent {
+        gamepad,
+        touchpad: touchpad as u32,
+        finger: finger as u32,
+        phase,
+        position: Vec2::new(x, y),
+        pressure,
+        timestamp,
+    });
+}
+
+fn convert_sdl_button(button: SdlButton) -> GamepadButton {
+    match button {
+        SdlButton::South => GamepadButton::South,
+        SdlButton::East => GamepadButton::East,
+        SdlButton::North => GamepadButton::North,
+        SdlButton::West => GamepadButton::West,
+        SdlButton::LeftShoulder => GamepadButton::LeftTrigger,
+        SdlButton::RightShoulder => GamepadButton::RightTrigger,
+        SdlButton::Back => GamepadButton::Select,
+        SdlButton::Start => GamepadButton::Start,
+        SdlButton::Guide => GamepadButton::Mode,
+        SdlButton::LeftStick => GamepadButton::LeftThumb,
+        SdlButton::RightStick => GamepadButton::RightThumb,
+        SdlButton::DPadUp => GamepadButton::DPadUp,
+        SdlButton::DPadDown => GamepadButton::DPadDown,
+        SdlButton::DPadLeft => GamepadButton::DPadLeft,
+        SdlButton::DPadRight => GamepadButton::DPadRight,
+        // misc buttons, paddles and the touchpad click keep their SDL index
+        button => GamepadButton::Other(button as u8),
+    }
+}
+
+/// Rumbles the triggers of a gamepad, which is supported by some Xbox controllers. Replaces the
+/// trigger rumble that is currently playing.
+///
+/// This is separate from the motor rumble of [`GamepadRumbleRequest`]. Send a request with an
+/// intensity of 0 to stop the triggers.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct GamepadTriggerRumbleRequest {
+    pub gamepad: Entity,
+    /// Intensity of the left trigger from `0.0` to `1.0`.
+    pub left: f32,
+    /// Intensity of the right trigger from `0.0` to `1.0`.
+    pub right: f32,
+    pub duration: Duration,
+}
+
+/// A rumble added by a [`GamepadRumbleRequest`] that is still playing.
+#[derive(Debug, Clone, Copy)]
+pub struct RunningRumble {
+    intensity: GamepadRumbleIntensity,
+    /// The SDL timestamp the rumble ends at.
+    end: u64,
+}
+
+/// system to play [`GamepadRumbleRequest`]s and [`GamepadTriggerRumbleRequest`]s
+///
+/// SDL only plays a single rumble at a time, so the intensities of the rumbles that are playing
+/// are added up and sent to SDL again whenever a rumble starts or ends.
+pub fn rumble_gamepads(
+    mut requests: EventReader<GamepadRumbleRequest>,
+    mut trigger_requests: EventReader<GamepadTriggerRumbleRequest>,
+    gamepads: Query<&SdlGamepad>,
+    mut running: Local<HashMap<Entity, Vec<RunningRumble>>>,
+    // gamepads need to be rumbled on the main thread
+    _non_send: NonSendMarker,
+) {
+    let now = sdl_ticks_ns();
+    let mut changed = Vec::new();
+
+    for request in requests.read() {
+        let gamepad = request.gamepad();
+        let rumbles = running.entry(gamepad).or_default();
+        match *request {
+            GamepadRumbleRequest::Add {
+                duration,
+                intensity,
+                ..
+            } => rumbles.push(RunningRumble {
+                intensity,
+                end: now.saturating_add(duration.as_nanos().try_into().unwrap_or(u64::MAX)),
+            }),
+            GamepadRumbleRequest::Stop { .. } => rumbles.clear(),
+        }
+        changed.push(gamepad);
+    }
+
+    for (gamepad, rumbles) in running.iter_mut() {
+        let len = rumbles.len();
+        rumbles.retain(|rumble| rumble.end > now);
+        if rumbles.len() != len {
+            changed.push(*gamepad);
+        }
+    }
+
+    SDL_CONTEXT.with_borrow_mut(|context| {
+        let Some(context) = context.as_mut() else {
+            return;
+        };
+
+        for gamepad in changed {
+            let Some(sdl_gamepad) = gamepads
+                .get(gamepad)
+                .ok()
+                .and_then(|SdlGamepad { id }| context.gamepads.get_mut(id))
+            else {
+                continue;
+            };
+            let rumbles = &running[&gamepad];
+
+            let strong = rumbles.iter().map(|r| r.intensity.strong_motor).sum();
+            let weak = rumbles.iter().map(|r| r.intensity.weak_motor).sum();
+            // play until the first rumble ends, the intensity is updated at that point
+            let duration_ms = rumbles
+                .iter()
+                .map(|rumble| (rumble.end - now).div_ceil(1_000_000))
+                .min()
+                .unwrap_or(0);
+            let _ = sdl_gamepad.set_rumble(
+                motor_intensity(strong),
+                motor_intensity(weak),
+                duration_ms.try_into().unwrap_or(u32::MAX),
+            );
+        }
+
+        for request in trigger_requests.read() {
+            let Some(sdl_gamepad) = gamepads
+                .get(request.gamepad)
+                .ok()
+                .and_then(|SdlGamepad { id }| context.gamepads.get_mut(id))
+            else {
+                continue;
+            };
+            let _ = sdl_gamepad.set_rumble_triggers(
+                motor_intensity(request.left),
+                motor_intensity(request.right),
+                request.duration.as_millis().try_into().unwrap_or(u32::MAX),
+            );
+        }
+    });
+
+    running.retain(|_, rumbles| !rumbles.is_empty());
+}
+
+fn motor_intensity(intensity: f32) -> u16 {
+    (intensity.clamp(0.0, 1.0) * f32::from(u16::MAX)) as u16
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        ffi::c_void,
+        sync::atomic::{AtomicU32, Ordering},
+        time::Duration,
+    };
+
+    use bevy_input::gamepad::{GamepadRumbleIntensity, GamepadRumbleRequest};
+    use bevy_math::Vec3;
+    use sdl3::sys::{
+        joystick::{
+            SDL_GetJoystickFromID, SDL_SendJoystickVirtualSensorData, SDL_VirtualJoystickDesc,
+            SDL_VirtualJoystickSensorDesc,
+        },
+        sensor::SDL_SensorType,
+    };
+
+    use super::{
+        GamepadInfo, GamepadLed, GamepadSensorEvent, GamepadSensorType, GamepadSensors, SdlGamepad,
+    };
+    use crate::{
+        devices::InputDevices,
+        test_app::{TestApp, virtual_gamepad_desc},
+    };
+
+    /// The last rumble SDL sent to the virtual gamepad, as low << 16 | high.
+    static RUMBLE: AtomicU32 = AtomicU32::new(u32::MAX);
+
+    unsafe extern "C" fn rumble(_userdata: *mut c_void, low: u16, high: u16) -> bool {
+        RUMBLE.store(u32::from(low) << 16 | u32::from(high), Ordering::Relaxed);
+        true
+    }
+
+    fn last_rumble() -> (u16, u16) {
+        let rumble = RUMBLE.load(Ordering::Relaxed);
+        ((rumble >> 16) as u16, rumble as u16)
+    }
+
+    #[test]
+    fn rumble_virtual_gamepad() {
+        let mut app = TestApp::new(&[]);
+        let id = app.attach_virtual_joystick(&SDL_VirtualJoystickDesc {
+            Rumble: Some(rumble),
+            ..virtual_gamepad_desc(c"rumble test")
+        });
+        app.update();
+
+        let gamepad = app
+            .world()
+            .resource::<InputDevices>()
+            .gamepad(id)
+            .expect("virtual gamepad was not added");
+        assert_eq!(
+            app.world().get::<SdlGamepad>(gamepad),
+            Some(&SdlGamepad { id })
+        );
+
+        app.world_mut().send_event(GamepadRumbleRequest::Add {
+            gamepad,
+            duration: Duration::from_secs(10),
+            intensity: GamepadRumbleIntensity {
+                strong_motor: 1.0,
+                weak_motor: 0.5,
+            },
+        });
+        app.update();
+        // the strong motor is the low frequency motor
+        assert_eq!(last_rumble(), (u16::MAX, u16::MAX / 2));
+
+        // a second rumble adds to the first one
+        app.world_mut().send_event(GamepadRumbleRequest::Add {
+            gamepad,
+            duration: Duration::from_secs(10),
+            intensity: GamepadRumbleIntensity::weak_motor(0.25),
+        });
+        app.update();
+        assert_eq!(
+            last_rumble(),
+            (u16::MAX, (0.75 * f32::from(u16::MAX)) as u16)
+        );
+
+        app.world_mut()
+            .send_event(GamepadRumbleRequest::Stop { gamepad });
+        app.update();
+        assert_eq!(last_rumble(), (0, 0));
+
+        // the components of the gamepad are removed with it
+        app.world_mut().entity_mut(gamepad).insert(GamepadLed {
+            red: 255,
+            green: 0,
+            blue: 0,
+        });
+        app.detach_virtual_joystick(id);
+        app.update();
+        assert_eq!(app.world().resource::<InputDevices>().gamepad(id), None);
+        let entity = app.world().entity(gamepad);
+        assert!(!entity.contains::<SdlGamepad>());
+        assert!(!entity.contains::<GamepadInfo>());
+        assert!(!entity.contains::<GamepadLed>());
+    }
+
+    #[test]
+    fn virtual_gamepad_sensors() {
+        let mut app = TestApp::new(&[]);
+        let sensors = [
+            SDL_VirtualJoystickSensorDesc {
+                r#type: SDL_SensorType::ACCEL,
+                rate: 100.0,
+            },
+            SDL_VirtualJoystickSensorDesc {
+                r#type: SDL_SensorType::GYRO,
+                rate: 100.0,
+            },
+        ];
+        let id = app.attach_virtual_joystick(&SDL_VirtualJoystickDesc {
+            nsensors: sensors.len() as u16,
+            sensors: sensors.as_ptr(),
+            ..virtual_gamepad_desc(c"sensor test")
+        });
+        app.update();
+
+        let gamepad = app
+            .world()
+            .resource::<InputDevices>()
+            .gamepad(id)
+            .expect("virtual gamepad was not added");
+        let mut gamepad_sensors = *app.world().get::<GamepadSensors>(gamepad).unwrap();
+        assert!(gamepad_sensors.has_accelerometer());
+        assert!(gamepad_sensors.has_gyroscope());
+
+        let send_readings = |timestamp| {
+            // resting level with the face buttons up, and turning around the vertical axis
+            let gravity = [0.0, 9.80665, 0.0];
+            let rotation = [0.0, 1.5, 0.0];
+            // SAFETY: called on the thread that initialized SDL, the gamepad is open
+            unsafe {
+                let joystick = SDL_GetJoystickFromID(id);
+                SDL_SendJoystickVirtualSensorData(
+                    joystick,
+                    SDL_SensorType::ACCEL,
+                    timestamp,
+                    gravity.as_ptr(),
+                    3,
+                );
+                SDL_SendJoystickVirtualSensorData(
+                    joystick,
+                    SDL_SensorType::GYRO,
+                    timestamp,
+                    rotation.as_ptr(),
+                    3,
+                );
+            }
+        };
+
+        // sensors are disabled by default
+        send_readings(1_000);
+        app.update();
+        assert!(app.take_events::<GamepadSensorEvent>().is_empty());
+
+        gamepad_sensors.accelerometer = true;
+        app.world_mut().entity_mut(gamepad).insert(gamepad_sensors);
+        app.update();
+        send_readings(2_000);
+        app.update();
+        let readings: Vec<_> = app
+            .take_events::<GamepadSensorEvent>()
+            .into_iter()
+            .map(|event| {
+                (
+                    event.gamepad,
+                    event.sensor,
+                    event.data,
+                    event.sensor_timestamp,
+                )
+            })
+            .collect();
+        assert_eq!(
+            readings,
+            [(
+                gamepad,
+                GamepadSensorType::Accelerometer,
+                Vec3::new(0.0, 9.80665, 0.0),
+                2_000
+            )]
+        );
+
+        gamepad_sensors.gyroscope = true;
+        app.world_mut().entity_mut(gamepad).insert(gamepad_sensors);
+        app.update();
+        send_readings(3_000);
+        app.update();
+        let readings: Vec<_> = app
+            .take_events::<GamepadSensorEvent>()
+            .into_iter()
+            .map(|event| (event.sensor, event.data))
+            .collect();
+        assert_eq!(
+            readings,
+            [
+                (
+                    GamepadSensorType::Accelerometer,
+                    Vec3::new(0.0, 9.80665, 0.0)
+                ),
+                (GamepadSensorType::Gyroscope, Vec3::new(0.0, 1.5, 0.0)),
+            ]
+        );
+    }
+}
@@ -0,0 +1,187 @@
+use std::path::PathBuf;
+
+use bevy_ecs::{
+    entity::Entity,
+    event::{Event, EventReader},
+    world::World,
+};
+use sdl3::GamepadSubsystem;
+use tracing::{info, warn};
+
+use crate::{
+    SDL_CONTEXT,
+    devices::InputDevices,
+    gamepad::{GamepadInfo, handle_gamepad_added},
+    non_send_marker::NonSendMarker,
+};
+
+/// Gamepad mappings in the [GameControllerDB](https://github.com/mdqinc/SDL_GameControllerDB)
+/// format, used by SDL to support controllers it doesn't recognize.
+///
+/// Mappings can be added at startup with [`Sdl3Settings::gamepad_mappings`](crate::Sdl3Settings)
+/// and at runtime with [`AddGamepadMappings`]. Mappings for other platforms and lines starting
+/// with `#` are ignored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GamepadMappings {
+    /// One or more mappings, one per line.
+    Mappings(String),
+    /// A file of mappings, like `gamecontrollerdb.txt`.
+    File(PathBuf),
+}
+
+impl GamepadMappings {
+    fn add(&self, gamepad: &GamepadSubsystem) {
+        let result = match self {
+            GamepadMappings::Mappings(mappings) => {
+                gamepad.load_mappings_from_read(&mut mappings.as_bytes())
+            }
+            GamepadMappings::File(path) => gamepad.load_mappings(path),
+        };
+        match result {
+            Ok(count) => info!("added {count} gamepad mappings"),
+            Err(err) => warn!("failed to add gamepad mappings: {err}"),
+        }
+    }
+}
+
+/// Adds gamepad mappings at runtime. Gamepads that are already connected and use one of the
+/// mappings are remapped, see [`GamepadRemapped`]. Joysticks that are turned into gamepads by a
+/// mapping are connected as new gamepads.
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct AddGamepadMappings(pub GamepadMappings);
+
+/// Sent when the mapping of a connected gamepad changes, e.g. after [`AddGamepadMappings`].
+#[derive(Event, Debug, Clone, PartialEq, Eq)]
+pub struct GamepadRemapped {
+    /// The entity of the gamepad.
+    pub gamepad: Entity,
+    /// The new mapping of the gamepad.
+    pub mapping: Option<String>,
+    /// SDL timestamp in nanoseconds.
+    pub timestamp: u64,
+}
+
+/// Adds mappings from the main thread, while building the plugin.
+pub fn add_startup_gamepad_mappings(mappings: &[GamepadMappings]) {
+    SDL_CONTEXT.with_borrow(|context| {
+        let Some(context) = context.as_ref() else {
+            return;
+        };
+        for mappings in mappings {
+            mappings.add(&context.gamepad);
+        }
+    });
+}
+
+/// system to add the mappings of [`AddGamepadMappings`] events
+pub fn add_gamepad_mappings(
+    mut events: EventReader<AddGamepadMappings>,
+    // mappings need to be added on the main thread
+    _non_send: NonSendMarker,
+) {
+    if events.is_empty() {
+        return;
+    }
+    SDL_CONTEXT.with_borrow(|context| {
+        let Some(context) = context.as_ref() else {
+            return;
+        };
+        for AddGamepadMappings(mappings) in events.read() {
+            mappings.add(&context.gamepad);
+        }
+    });
+}
+
+pub fn handle_gamepad_remapped(world: &mut World, timestamp: u64, which: u32) {
+    let Some(entity) = world.resource::<InputDevices>().gamepad(which) else {
+        // a joystick that wasn't opened as a gamepad yet
+        handle_gamepad_added(world, timestamp, which);
+        return;
+    };
+    let Some((mapping, name)) = SDL_CONTEXT.with_borrow(|context| {
+        let gamepad = context.as_ref()?.gamepads.get(&which)?;
+        Some((gamepad.mapping(), gamepad.name()))
+    }) else {
+        return;
+    };
+
+    // the name of a gamepad comes from its mapping
+    if let Some(name) = name
+        && let Some(mut info) = world.get_mut::<GamepadInfo>(entity)
+    {
+        info.name = name;
+    }
+
+    world.send_event(GamepadRemapped {
+        gamepad: entity,
+        mapping,
+        timestamp,
+    });
+}
+
+#[cfg(feature = "bevy_asset")]
+pub use self::asset::{GamepadMappingsAsset, GamepadMappingsLoader, add_gamepad_mapping_assets};
+
+#[cfg(feature = "bevy_asset")]
+mod asset {
+    use bevy_asset::{Asset, AssetEvent, AssetLoader, Assets, LoadContext, io::Reader};
+    use bevy_ecs::{event::EventReader, system::Res};
+    use bevy_reflect::TypePath;
+
+    use super::GamepadMappings;
+    use crate::{SDL_CONTEXT, non_send_marker::NonSendMarker};
+
+    /// A file of gamepad mappings loaded as an asset, see [`GamepadMappings`]. The mappings are
+    /// added when the asset is loaded and again when it is modified.
+    ///
+    /// The loader isn't registered for any extension, so load it with its type, e.g.
+    /// `asset_server.load::<GamepadMappingsAsset>("gamecontrollerdb.txt")`, and keep the handle
+    /// around until it is loaded.
+    #[derive(Asset, TypePath, Debug, Clone)]
+    pub struct GamepadMappingsAsset(pub String);
+
+    #[derive(Default)]
+    pub struct GamepadMappingsLoader;
+
+    impl AssetLoader for GamepadMappingsLoader {
+        type Asset = GamepadMappingsAsset;
+        type Settings = ();
+        type Error = std::io::Error;
+
+        async fn load(
+            &self,
+            reader: &mut dyn Reader,
+            _settings: &(),
+            _load_context: &mut LoadContext<'_>,
+        ) -> Result<GamepadMappingsAsset, std::io::Error> {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+            Ok(GamepadMappingsAsset(
+                String::from_utf8_lossy(&bytes).into_owned(),
+            ))
+        }
+    }
+
+    /// system to add the mappings of loaded and modified [`GamepadMappingsAsset`]s
+    pub fn add_gamepad_mapping_assets(
+        mut events: EventReader<AssetEvent<GamepadMappingsAsset>>,
+        assets: Res<Assets<GamepadMappingsAsset>>,
+        // mappings need to be added on the main thread
+        _non_send: NonSendMarker,
+    ) {
+        for event in events.read() {
+            let (AssetEvent::LoadedWithDependencies { id } | AssetEvent::Modified { id }) = event
+            else {
+                continue;
+            };
+            let Some(GamepadMappingsAsset(mappings)) = assets.get(*id) else {
+                continue;
+            };
+            SDL_CONTEXT.with_borrow(|context| {
+                if let Some(context) = context.as_ref() {
+                    GamepadMappings::Mappings(mappings.clone()).add(&context.gamepad);
+                }
+            });
+        }
+    }
+}
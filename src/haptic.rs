@@ -0,0 +1,664 @@
+use std::time::Duration;
+
+use bevy_ecs::{
+    component::Component,
+    entity::Entity,
+    event::{Event, EventReader},
+    name::Name,
+    query::Changed,
+    removal_detection::RemovedComponents,
+    system::Query,
+    world::World,
+};
+use bevy_math::Vec3;
+use sdl3::sys::haptic::*;
+use tracing::warn;
+
+use crate::{
+    SDL_CONTEXT,
+    devices::{InputDeviceEvent, InputDevices, sdl_id_list, sdl_name},
+    non_send_marker::NonSendMarker,
+};
+
+/// A haptic device, like a force feedback wheel or joystick. Spawned with a [`Name`] and
+/// [`HapticInfo`] when the device is connected and despawned when it is removed.
+///
+/// Effects are played by spawning a [`HapticEffect`] for the device and sending
+/// [`HapticRequest`]s. Gamepad rumble doesn't need this, see
+/// [`GamepadRumbleRequest`](bevy_input::gamepad::GamepadRumbleRequest).
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SdlHaptic {
+    /// The SDL haptic id of the device.
+    pub id: u32,
+}
+
+/// What a haptic device supports.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HapticInfo {
+    /// The number of axes effects can be played on.
+    pub num_axes: u32,
+    /// How many effects can be uploaded at once.
+    pub max_effects: u32,
+    /// How many effects can play at once.
+    pub max_effects_playing: u32,
+    features: u32,
+}
+
+impl HapticInfo {
+    /// Returns `true` if the device can play this kind of effect.
+    pub fn supports(&self, effect: &HapticEffectKind) -> bool {
+        self.features & u32::from(effect.sdl_type()) != 0
+    }
+
+    /// Returns `true` if the gain can be set with [`HapticRequest::SetGain`].
+    pub fn supports_gain(&self) -> bool {
+        self.features & SDL_HAPTIC_GAIN != 0
+    }
+
+    /// Returns `true` if autocenter can be set with [`HapticRequest::SetAutocenter`].
+    pub fn supports_autocenter(&self) -> bool {
+        self.features & SDL_HAPTIC_AUTOCENTER != 0
+    }
+}
+
+/// A force feedback effect for a [`SdlHaptic`] device. The effect is uploaded to the device when
+/// this component is added, updated when it changes and destroyed when it is removed, so
+/// parameters like the force of a spring can be changed while the effect plays.
+#[derive(Component, Debug, Clone, PartialEq)]
+pub struct HapticEffect {
+    /// The entity of the [`SdlHaptic`] device.
+    pub haptic: Entity,
+    pub kind: HapticEffectKind,
+    /// How long the effect plays, or `None` to play until stopped.
+    pub length: Option<Duration>,
+    /// How long to wait before playing the effect.
+    pub delay: Duration,
+}
+
+/// The kind of a [`HapticEffect`]. Levels and magnitudes range from `0.0` to `1.0`, or from
+/// `-1.0` to `1.0` if they have a direction.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HapticEffectKind {
+    /// A constant force.
+    Constant {
+        direction: HapticDirection,
+        level: f32,
+        envelope: HapticEnvelope,
+    },
+    /// A force that follows a wave.
+    Periodic {
+        waveform: HapticWaveform,
+        direction: HapticDirection,
+        period: Duration,
+        magnitude: f32,
+        /// Shifts the wave up or down.
+        offset: f32,
+        /// Where in the wave the effect starts, in degrees.
+        phase: f32,
+        envelope: HapticEnvelope,
+    },
+    /// A force that increases with the distance from the center.
+    Spring(HapticCondition),
+    /// A force that increases with the velocity.
+    Damper(HapticCondition),
+    /// A force that increases with the acceleration.
+    Inertia(HapticCondition),
+    /// A force that opposes movement.
+    Friction(HapticCondition),
+    /// A force that changes linearly from `start` to `end` over the length of the effect.
+    Ramp {
+        direction: HapticDirection,
+        start: f32,
+        end: f32,
+        envelope: HapticEnvelope,
+    },
+    /// Rumble of the large and small motor.
+    LeftRight { large: f32, small: f32 },
+}
+
+impl HapticEffectKind {
+    fn sdl_type(&self) -> u16 {
+        match self {
+            HapticEffectKind::Constant { .. } => SDL_HAPTIC_CONSTANT,
+            HapticEffectKind::Periodic { waveform, .. } => match waveform {
+                HapticWaveform::Sine => SDL_HAPTIC_SINE,
+                HapticWaveform::Square => SDL_HAPTIC_SQUARE,
+                HapticWaveform::Triangle => SDL_HAPTIC_TRIANGLE,
+                HapticWaveform::SawtoothUp => SDL_HAPTIC_SAWTOOTHUP,
+                HapticWaveform::SawtoothDown => SDL_HAPTIC_SAWTOOTHDOWN,
+            },
+            HapticEffectKind::Spring(_) => SDL_HAPTIC_SPRING,
+            HapticEffectKind::Damper(_) => SDL_HAPTIC_DAMPER,
+            HapticEffectKind::Inertia(_) => SDL_HAPTIC_INERTIA,
+            HapticEffectKind::Friction(_) => SDL_HAPTIC_FRICTION,
+            HapticEffectKind::Ramp { .. } => SDL_HAPTIC_RAMP,
+            HapticEffectKind::LeftRight { .. } => SDL_HAPTIC_LEFTRIGHT,
+        }
+    }
+}
+
+/// The direction a force comes from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum HapticDirection {
+    /// Degrees clockwise, with 0 being north (away from the user).
+    Polar(f32),
+    /// A direction vector, with x pointing east, y pointing north and z pointing up.
+    Cartesian(Vec3),
+    /// Along the steering axis of a wheel, positive values turn it to the right.
+    Steering,
+}
+
+impl Default for HapticDirection {
+    fn default() -> Self {
+        HapticDirection::Polar(0.0)
+    }
+}
+
+/// The shape of a [`HapticEffectKind::Periodic`] effect.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum HapticWaveform {
+    #[default]
+    Sine,
+    Square,
+    Triangle,
+    SawtoothUp,
+    SawtoothDown,
+}
+
+/// Fades an effect in and out. The default doesn't change the effect.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct HapticEnvelope {
+    pub attack_length: Duration,
+    /// The level the effect starts at, from `0.0` to `1.0`.
+    pub attack_level: f32,
+    pub fade_length: Duration,
+    /// The level the effect ends at, from `0.0` to `1.0`.
+    pub fade_level: f32,
+}
+
+/// The parameters of a condition effect like a spring, applied to every axis of the device.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct HapticCondition {
+    /// The maximum force to the right, from `0.0` to `1.0`.
+    pub right_saturation: f32,
+    /// The maximum force to the left, from `0.0` to `1.0`.
+    pub left_saturation: f32,
+    /// How fast the force increases to the right, from `-1.0` to `1.0`.
+    pub right_coefficient: f32,
+    /// How fast the force increases to the left, from `-1.0` to `1.0`.
+    pub left_coefficient: f32,
+    /// The size of the dead zone around the center, from `0.0` to `1.0`.
+    pub deadband: f32,
+    /// The position of the center, from `-1.0` to `1.0`.
+    pub center: f32,
+}
+
+/// Controls the effects of a [`SdlHaptic`] device.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub enum HapticRequest {
+    /// Plays the [`HapticEffect`] of an entity a number of times, or until stopped if
+    /// `iterations` is `None`.
+    Play {
+        effect: Entity,
+        iterations: Option<u32>,
+    },
+    /// Stops the [`HapticEffect`] of an entity.
+    Stop { effect: Entity },
+    /// Stops all effects of a [`SdlHaptic`] device.
+    StopAll { haptic: Entity },
+    /// Sets the overall strength of a [`SdlHaptic`] device, from `0.0` to `1.0`.
+    SetGain { haptic: Entity, gain: f32 },
+    /// Sets how strongly a [`SdlHaptic`] device returns to its center by itself, from `0.0` to
+    /// `1.0`. Usually turned off when playing a spring effect.
+    SetAutocenter { haptic: Entity, autocenter: f32 },
+}
+
+/// Spawns and despawns haptic entities to match the haptic devices SDL knows about.
+///
+/// SDL has no events for haptic devices, but they are joysticks as well, so this is called when
+/// joysticks are added or removed.
+pub fn sync_haptic_devices(world: &mut World) {
+    let has_haptics = SDL_CONTEXT.with_borrow(|context| {
+        context
+            .as_ref()
+            .is_some_and(|context| context.haptic.is_some())
+    });
+    if !has_haptics {
+        return;
+    }
+    // SAFETY: only called on the main thread, the list is freed by `sdl_id_list`
+    let haptics = unsafe { sdl_id_list(SDL_GetHaptics) };
+
+    let mut devices = world.resource::<InputDevices>().clone();
+
+    for (id, entity) in devices.haptics.clone() {
+        if !haptics.contains(&id) {
+            devices.haptics.remove(&id);
+            SDL_CONTEXT.with_borrow_mut(|context| {
+                let Some(context) = context.as_mut() else {
+                    return;
+                };
+                // closing the device destroys its effects
+                context
+                    .haptic_effects
+                    .retain(|_, (haptic, _)| *haptic != id);
+                if let Some(haptic) = context.haptics.remove(&id) {
+                    // SAFETY: the device was opened by us
+                    unsafe { SDL_CloseHaptic(haptic) };
+                }
+            });
+            world.send_event(InputDeviceEvent::HapticRemoved(entity));
+            world.despawn(entity);
+        }
+    }
+    for id in haptics {
+        if devices.haptics.contains_key(&id) {
+            continue;
+        }
+        // SAFETY: only called on the main thread, the device is checked after opening
+        let haptic = unsafe { SDL_OpenHaptic(id) };
+        if haptic.is_null() {
+            warn!("failed to open haptic device: {}", sdl3::get_error());
+            continue;
+        }
+        // SAFETY: the device is open, the name is owned by SDL
+        let (name, info) = unsafe {
+            (
+                sdl_name(SDL_GetHapticName(haptic)),
+                HapticInfo {
+                    num_axes: SDL_GetNumHapticAxes(haptic).max(0) as u32,
+                    max_effects: SDL_GetMaxHapticEffects(haptic).max(0) as u32,
+                    max_effects_playing: SDL_GetMaxHapticEffectsPlaying(haptic).max(0) as u32,
+                    features: SDL_GetHapticFeatures(haptic),
+                },
+            )
+        };
+        SDL_CONTEXT.with_borrow_mut(|context| {
+            if let Some(context) = context.as_mut() {
+                context.haptics.insert(id, haptic);
+            }
+        });
+        let entity = world.spawn((SdlHaptic { id }, Name::new(name), info)).id();
+        devices.haptics.insert(id, entity);
+        world.send_event(InputDeviceEvent::HapticAdded(entity));
+    }
+
+    world.insert_resource(devices);
+}
+
+/// system to upload [`HapticEffect`]s and handle [`HapticRequest`]s
+pub fn update_haptic_effects(
+    effects: Query<(Entity, &HapticEffect), Changed<HapticEffect>>,
+    mut removed: RemovedComponents<HapticEffect>,
+    haptics: Query<&SdlHaptic>,
+    mut requests: EventReader<HapticRequest>,
+    // haptic devices need to be updated on the main thread
+    _non_send: NonSendMarker,
+) {
+    SDL_CONTEXT.with_borrow_mut(|context| {
+        let Some(context) = context.as_mut() else {
+            return;
+        };
+
+        for entity in removed.read() {
+            if let Some((id, effect)) = context.haptic_effects.remove(&entity)
+                && let Some(&haptic) = context.haptics.get(&id)
+            {
+                // SAFETY: only called on the main thread, the device is open
+                unsafe { SDL_DestroyHapticEffect(haptic, effect) };
+            }
+        }
+
+        for (entity, effect) in &effects {
+            let Ok(&SdlHaptic { id }) = haptics.get(effect.haptic) else {
+                continue;
+            };
+            let Some(&haptic) = context.haptics.get(&id) else {
+                continue;
+            };
+            let sdl_effect = effect.to_sdl();
+
+            // SAFETY: only called on the main thread, the device is open
+            unsafe {
+                match context.haptic_effects.get(&entity) {
+                    // effects can be updated while they play, as long as their kind is the same
+                    Some(&(old_id, old_effect))
+                        if old_id == id
+                            && SDL_UpdateHapticEffect(haptic, old_effect, &sdl_effect) =>
+                    {
+                        continue;
+                    }
+                    Some(&(old_id, old_effect)) => {
+                        if let Some(&old_haptic) = context.haptics.get(&old_id) {
+                            SDL_DestroyHapticEffect(old_haptic, old_effect);
+                        }
+                        context.haptic_effects.remove(&entity);
+                    }
+                    None => {}
+                }
+                let created = SDL_CreateHapticEffect(haptic, &sdl_effect);
+                if created < 0 {
+                    warn!("failed to upload haptic effect: {}", sdl3::get_error());
+                    continue;
+                }
+                context.haptic_effects.insert(entity, (id, created));
+            }
+        }
+
+        for request in requests.read() {
+            let haptic_of = |entity: Entity| {
+                let SdlHaptic { id } = haptics.get(entity).ok()?;
+                context.haptics.get(id).copied()
+            };
+            let effect_of = |entity: Entity| {
+                let (id, effect) = context.haptic_effects.get(&entity)?;
+                Some((*context.haptics.get(id)?, *effect))
+            };
+
+            // SAFETY: only called on the main thread, the devices are open
+            unsafe {
+                match *request {
+                    HapticRequest::Play { effect, iterations } => {
+                        if let Some((haptic, effect)) = effect_of(effect) {
+                            let iterations = iterations.unwrap_or(SDL_HAPTIC_INFINITY);
+                            SDL_RunHapticEffect(haptic, effect, iterations);
+                        }
+                    }
+                    HapticRequest::Stop { effect } => {
+                        if let Some((haptic, effect)) = effect_of(effect) {
+                            SDL_StopHapticEffect(haptic, effect);
+                        }
+                    }
+                    HapticRequest::StopAll { haptic } => {
+                        if let Some(haptic) = haptic_of(haptic) {
+                            SDL_StopHapticEffects(haptic);
+                        }
+                    }
+                    HapticRequest::SetGain { haptic, gain } => {
+                        if let Some(haptic) = haptic_of(haptic) {
+                            SDL_SetHapticGain(haptic, percent(gain));
+                        }
+                    }
+                    HapticRequest::SetAutocenter { haptic, autocenter } => {
+                        if let Some(haptic) = haptic_of(haptic) {
+                            SDL_SetHapticAutocenter(haptic, percent(autocenter));
+                        }
+                    }
+                }
+            }
+        }
+    });
+}
+
+impl HapticEffect {
+    fn to_sdl(&self) -> SDL_HapticEffect {
+        let r#type = self.kind.sdl_type();
+        let length = self.length.map_or(SDL_HAPTIC_INFINITY, |length| {
+            length.as_millis().min(u128::from(SDL_HAPTIC_INFINITY - 1)) as u32
+        });
+        let delay = millis(self.delay);
+
+        match self.kind {
+            HapticEffectKind::Constant {
+                direction,
+                level,
+                envelope,
+            } => SDL_HapticEffect {
+                constant: SDL_HapticConstant {
+                    r#type,
+                    direction: direction.to_sdl(),
+                    length,
+                    delay,
+                    level: signed(level),
+                    attack_length: millis(envelope.attack_length),
+                    attack_level: unsigned(envelope.attack_level),
+                    fade_length: millis(envelope.fade_length),
+                    fade_level: unsigned(envelope.fade_level),
+                    ..Default::default()
+                },
+            },
+            HapticEffectKind::Periodic {
+                direction,
+                period,
+                magnitude,
+                offset,
+                phase,
+                envelope,
+                ..
+            } => SDL_HapticEffect {
+                periodic: SDL_HapticPeriodic {
+                    r#type,
+                    direction: direction.to_sdl(),
+                    length,
+                    delay,
+                    period: millis(period),
+                    magnitude: signed(magnitude),
+                    offset: signed(offset),
+                    // hundredths of a degree
+                    phase: (phase.rem_euclid(360.0) * 100.0) as u16,
+                    attack_length: millis(envelope.attack_length),
+                    attack_level: unsigned(envelope.attack_level),
+                    fade_length: millis(envelope.fade_length),
+                    fade_level: unsigned(envelope.fade_level),
+                    ..Default::default()
+                },
+            },
+            HapticEffectKind::Spring(condition)
+            | HapticEffectKind::Damper(condition)
+            | HapticEffectKind::Inertia(condition)
+            | HapticEffectKind::Friction(condition) => SDL_HapticEffect {
+                condition: SDL_HapticCondition {
+                    r#type,
+                    length,
+                    delay,
+                    right_sat: [unsigned(condition.right_saturation); 3],
+                    left_sat: [unsigned(condition.left_saturation); 3],
+                    right_coeff: [signed(condition.right_coefficient); 3],
+                    left_coeff: [signed(condition.left_coefficient); 3],
+                    deadband: [unsigned(condition.deadband); 3],
+                    center: [signed(condition.center); 3],
+                    ..Default::default()
+                },
+            },
+            HapticEffectKind::Ramp {
+                direction,
+                start,
+                end,
+                envelope,
+            } => SDL_HapticEffect {
+                ramp: SDL_HapticRamp {
+                    r#type,
+                    direction: direction.to_sdl(),
+                    length,
+                    delay,
+                    start: signed(start),
+                    end: signed(end),
+                    attack_length: millis(envelope.attack_length),
+                    attack_level: unsigned(envelope.attack_level),
+                    fade_length: millis(envelope.fade_length),
+                    fade_level: unsigned(envelope.fade_level),
+                    ..Default::default()
+                },
+            },
+            HapticEffectKind::LeftRight { large, small } => SDL_HapticEffect {
+                leftright: SDL_HapticLeftRight {
+                    r#type,
+                    length,
+                    large_magnitude: unsigned(large),
+                    small_magnitude: unsigned(small),
+                },
+            },
+        }
+    }
+}
+
+impl HapticDirection {
+    fn to_sdl(self) -> SDL_HapticDirection {
+        match self {
+            HapticDirection::Polar(degrees) => SDL_HapticDirection {
+                r#type: SDL_HAPTIC_POLAR,
+                // hundredths of a degree
+                dir: [(degrees.rem_euclid(360.0) * 100.0) as i32, 0, 0],
+            },
+            HapticDirection::Cartesian(direction) => {
+                // SDL's y axis points south
+                let direction = direction * Vec3::new(1.0, -1.0, 1.0) * f32::from(i16::MAX);
+                SDL_HapticDirection {
+                    r#type: SDL_HAPTIC_CARTESIAN,
+                    dir: [direction.x as i32, direction.y as i32, direction.z as i32],
+                }
+            }
+            HapticDirection::Steering => SDL_HapticDirection {
+                r#type: SDL_HAPTIC_STEERING_AXIS,
+                dir: [0; 3],
+            },
+        }
+    }
+}
+
+fn millis(duration: Duration) -> u16 {
+    duration.as_millis().min(u128::from(u16::MAX)) as u16
+}
+
+fn signed(value: f32) -> i16 {
+    (value.clamp(-1.0, 1.0) * f32::from(i16::MAX)) as i16
+}
+
+fn unsigned(value: f32) -> u16 {
+    (value.clamp(0.0, 1.0) * f32::from(u16::MAX)) as u16
+}
+
+fn percent(value: f32) -> i32 {
+    (value.clamp(0.0, 1.0) * 100.0).round() as i32
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use bevy_ecs::entity::Entity;
+    use bevy_math::Vec3;
+    use sdl3::sys::haptic::*;
+
+    use super::{
+        HapticCondition, HapticDirection, HapticEffect, HapticEffectKind, HapticEnvelope,
+        HapticWaveform,
+    };
+
+    fn effect(kind: HapticEffectKind, length: Option<Duration>) -> SDL_HapticEffect {
+        HapticEffect {
+            haptic: Entity::PLACEHOLDER,
+            kind,
+            length,
+            delay: Duration::from_millis(20),
+        }
+        .to_sdl()
+    }
+
+    #[test]
+    fn directions() {
+        let polar = HapticDirection::Polar(-90.0).to_sdl();
+        assert_eq!(polar.r#type, SDL_HAPTIC_POLAR);
+        assert_eq!(polar.dir, [27_000, 0, 0]);
+
+        // SDL's north is (0, -1, 0)
+        let north = HapticDirection::Cartesian(Vec3::Y).to_sdl();
+        assert_eq!(north.r#type, SDL_HAPTIC_CARTESIAN);
+        assert_eq!(north.dir, [0, -32_767, 0]);
+        let east = HapticDirection::Cartesian(Vec3::X).to_sdl();
+        assert_eq!(east.dir, [32_767, 0, 0]);
+
+        let steering = HapticDirection::Steering.to_sdl();
+        assert_eq!(steering.r#type, SDL_HAPTIC_STEERING_AXIS);
+    }
+
+    #[test]
+    fn constant_effect() {
+        let sdl = effect(
+            HapticEffectKind::Constant {
+                direction: HapticDirection::Polar(90.0),
+                level: -0.5,
+                envelope: HapticEnvelope {
+                    attack_length: Duration::from_millis(100),
+                    attack_level: 1.0,
+                    fade_length: Duration::from_secs(100),
+                    fade_level: 2.0,
+                },
+            },
+            Some(Duration::from_secs(1)),
+        );
+        // SAFETY: the effect is a constant effect
+        let constant = unsafe { sdl.constant };
+        assert_eq!(constant.r#type, SDL_HAPTIC_CONSTANT);
+        assert_eq!(constant.direction.dir, [9_000, 0, 0]);
+        assert_eq!(constant.length, 1_000);
+        assert_eq!(constant.delay, 20);
+        assert_eq!(constant.level, -16_383);
+        assert_eq!(constant.attack_length, 100);
+        assert_eq!(constant.attack_level, u16::MAX);
+        // out of range values are clamped
+        assert_eq!(constant.fade_length, u16::MAX);
+        assert_eq!(constant.fade_level, u16::MAX);
+    }
+
+    #[test]
+    fn periodic_effect() {
+        let sdl = effect(
+            HapticEffectKind::Periodic {
+                waveform: HapticWaveform::Triangle,
+                direction: HapticDirection::Cartesian(Vec3::NEG_Y),
+                period: Duration::from_millis(50),
+                magnitude: 1.0,
+                offset: -1.0,
+                phase: 450.0,
+                envelope: HapticEnvelope::default(),
+            },
+            None,
+        );
+        // SAFETY: the effect is a periodic effect
+        let periodic = unsafe { sdl.periodic };
+        assert_eq!(periodic.r#type, SDL_HAPTIC_TRIANGLE);
+        assert_eq!(periodic.direction.dir, [0, 32_767, 0]);
+        assert_eq!(periodic.length, SDL_HAPTIC_INFINITY);
+        assert_eq!(periodic.period, 50);
+        assert_eq!(periodic.magnitude, i16::MAX);
+        assert_eq!(periodic.offset, -i16::MAX);
+        assert_eq!(periodic.phase, 9_000);
+    }
+
+    #[test]
+    fn condition_and_rumble_effects() {
+        let sdl = effect(
+            HapticEffectKind::Spring(HapticCondition {
+                right_saturation: 1.0,
+                left_saturation: 0.0,
+                right_coefficient: 1.0,
+                left_coefficient: -1.0,
+                deadband: 0.0,
+                center: 0.0,
+            }),
+            None,
+        );
+        // SAFETY: the effect is a condition effect
+        let condition = unsafe { sdl.condition };
+        assert_eq!(condition.r#type, SDL_HAPTIC_SPRING);
+        assert_eq!(condition.right_sat, [u16::MAX; 3]);
+        assert_eq!(condition.left_sat, [0; 3]);
+        assert_eq!(condition.right_coeff, [i16::MAX; 3]);
+        assert_eq!(condition.left_coeff, [-i16::MAX; 3]);
+
+        let sdl = effect(
+            HapticEffectKind::LeftRight {
+                large: 1.0,
+                small: 0.5,
+            },
+            Some(Duration::from_millis(200)),
+        );
+        // SAFETY: the effect is a left/right effect
+        let leftright = unsafe { sdl.leftright };
+        assert_eq!(leftright.r#type, SDL_HAPTIC_LEFTRIGHT);
+        assert_eq!(leftright.length, 200);
+        assert_eq!(leftright.large_magnitude, u16::MAX);
+        assert_eq!(leftright.small_magnitude, 32_767);
+    }
+}
@@ -0,0 +1,492 @@
+use bevy_ecs::{component::Component, entity::Entity, event::Event, name::Name, world::World};
+use bevy_input::ButtonState;
+use bevy_math::Vec2;
+use sdl3::joystick::HatState;
+
+use crate::{
+    SDL_CONTEXT,
+    devices::{InputDeviceEvent, InputDevices},
+    power::Battery,
+};
+
+/// A joystick that isn't a gamepad, like a flight stick, wheel or pedals. Spawned with a
+/// [`Name`], [`JoystickInfo`], [`JoystickState`] and [`Battery`] when the joystick is connected
+/// and despawned when it is removed.
+///
+/// Joysticks SDL recognizes as gamepads are only exposed as bevy
+/// [`Gamepad`](bevy_input::gamepad::Gamepad)s, see [`SdlGamepad`](crate::SdlGamepad).
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SdlJoystick {
+    /// The SDL joystick id of the joystick.
+    pub id: u32,
+}
+
+/// What SDL knows about a joystick.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct JoystickInfo {
+    pub name: String,
+    pub vendor_id: Option<u16>,
+    pub product_id: Option<u16>,
+    pub num_axes: u32,
+    pub num_buttons: u32,
+    pub num_hats: u32,
+    pub num_balls: u32,
+}
+
+/// The current state of the axes, buttons and hats of a joystick, updated as joystick events are
+/// received.
+#[derive(Component, Debug, Clone, PartialEq, Default)]
+pub struct JoystickState {
+    axes: Vec<f32>,
+    buttons: Vec<bool>,
+    hats: Vec<JoystickHat>,
+}
+
+impl JoystickState {
+    /// The value of an axis between -1.0 and 1.0, or `None` if the joystick has no such axis.
+    pub fn axis(&self, axis: u8) -> Option<f32> {
+        self.axes.get(axis as usize).copied()
+    }
+
+    /// Returns `true` if the button is pressed.
+    pub fn pressed(&self, button: u8) -> bool {
+        self.buttons.get(button as usize).copied().unwrap_or(false)
+    }
+
+    /// The position of a hat, or `None` if the joystick has no such hat.
+    pub fn hat(&self, hat: u8) -> Option<JoystickHat> {
+        self.hats.get(hat as usize).copied()
+    }
+
+    pub fn axes(&self) -> &[f32] {
+        &self.axes
+    }
+
+    pub fn buttons(&self) -> &[bool] {
+        &self.buttons
+    }
+
+    pub fn hats(&self) -> &[JoystickHat] {
+        &self.hats
+    }
+}
+
+/// The position of a joystick hat, also known as a POV switch.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum JoystickHat {
+    #[default]
+    Centered,
+    Up,
+    Right,
+    Down,
+    Left,
+    RightUp,
+    RightDown,
+    LeftUp,
+    LeftDown,
+}
+
+impl From<HatState> for JoystickHat {
+    fn from(state: HatState) -> Self {
+        match state {
+            HatState::Centered => JoystickHat::Centered,
+            HatState::Up => JoystickHat::Up,
+            HatState::Right => JoystickHat::Right,
+            HatState::Down => JoystickHat::Down,
+            HatState::Left => JoystickHat::Left,
+            HatState::RightUp => JoystickHat::RightUp,
+            HatState::RightDown => JoystickHat::RightDown,
+            HatState::LeftUp => JoystickHat::LeftUp,
+            HatState::LeftDown => JoystickHat::LeftDown,
+        }
+    }
+}
+
+/// Sent when an axis of a [`SdlJoystick`] moves.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct JoystickAxisEvent {
+    pub joystick: Entity,
+    pub axis: u8,
+    /// The new value of the axis between -1.0 and 1.0.
+    pub value: f32,
+    /// SDL timestamp in nanoseconds.
+    pub timestamp: u64,
+}
+
+/// Sent when a button of a [`SdlJoystick`] is pressed or released.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JoystickButtonEvent {
+    pub joystick: Entity,
+    pub button: u8,
+    pub state: ButtonState,
+    /// SDL timestamp in nanoseconds.
+    pub timestamp: u64,
+}
+
+/// Sent when a hat of a [`SdlJoystick`] moves.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JoystickHatEvent {
+    pub joystick: Entity,
+    pub hat: u8,
+    pub position: JoystickHat,
+    /// SDL timestamp in nanoseconds.
+    pub timestamp: u64,
+}
+
+/// Sent when a trackball of a [`SdlJoystick`] moves. Trackballs only report relative motion.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct JoystickBallEvent {
+    pub joystick: Entity,
+    pub ball: u8,
+    pub delta: Vec2,
+    /// SDL timestamp in nanoseconds.
+    pub timestamp: u64,
+}
+
+pub fn handle_joystick_added(world: &mut World, which: u32) {
+    if world.resource::<InputDevices>().joystick(which).is_some() {
+        return;
+    }
+    let Some((info, state, battery)) = SDL_CONTEXT.with_borrow_mut(|context| {
+        let context = context.as_mut()?;
+        // SAFETY: only called on the main thread
+        if unsafe { sdl3::sys::gamepad::SDL_IsGamepad(which) } {
+            return None;
+        }
+        let joystick = context.joystick.open(which).ok()?;
+
+        // SAFETY: only called on the main thread, the joystick was just opened
+        let (vendor_id, product_id, num_balls) = unsafe {
+            use sdl3::sys::joystick::*;
+            (
+                SDL_GetJoystickVendorForID(which),
+                SDL_GetJoystickProductForID(which),
+                SDL_GetNumJoystickBalls(SDL_GetJoystickFromID(which)),
+            )
+        };
+        let info = JoystickInfo {
+            name: joystick.name(),
+            vendor_id: (vendor_id != 0).then_some(vendor_id),
+            product_id: (product_id != 0).then_some(product_id),
+            num_axes: joystick.num_axes(),
+            num_buttons: joystick.num_buttons(),
+            num_hats: joystick.num_hats(),
+            num_balls: num_balls.max(0) as u32,
+        };
+        let state = JoystickState {
+            axes: (0..info.num_axes)
+                .map(|axis| joystick.axis(axis).map_or(0.0, axis_value))
+                .collect(),
+            buttons: (0..info.num_buttons)
+                .map(|button| joystick.button(button).unwrap_or(false))
+                .collect(),
+            hats: (0..info.num_hats)
+                .map(|hat| joystick.hat(hat).map_or(JoystickHat::Centered, Into::into))
+                .collect(),
+        };
+
+        context.joysticks.insert(which, joystick);
+        Some((info, state, Battery::for_joystick(which)))
+    }) else {
+        return;
+    };
+
+    let entity = world
+        .spawn((
+            SdlJoystick { id: which },
+            Name::new(info.name.clone()),
+            info,
+            state,
+            battery,
+        ))
+        .id();
+    world
+        .resource_mut::<InputDevices>()
+        .joysticks
+        .insert(which, entity);
+    world.send_event(InputDeviceEvent::JoystickAdded(entity));
+}
+
+pub fn handle_joystick_removed(world: &mut World, which: u32) {
+    let Some(entity) = world
+        .resource_mut::<InputDevices>()
+        .joysticks
+        .remove(&which)
+    else {
+        return;
+    };
+    SDL_CONTEXT.with_borrow_mut(|context| {
+        if let Some(context) = context.as_mut() {
+            context.joysticks.remove(&which);
+        }
+    });
+    world.send_event(InputDeviceEvent::JoystickRemoved(entity));
+    world.despawn(entity);
+}
+
+pub fn handle_joystick_axis(world: &mut World, timestamp: u64, which: u32, axis: u8, value: i16) {
+    let Some(joystick) = world.resource::<InputDevices>().joystick(which) else {
+        return;
+    };
+    let value = axis_value(value);
+    if let Some(mut state) = world.get_mut::<JoystickState>(joystick)
+        && let Some(axis) = state.axes.get_mut(axis as usize)
+    {
+        *axis = value;
+    }
+    world.send_event(JoystickAxisEvent {
+        joystick,
+        axis,
+        value,
+        timestamp,
+    });
+}
+
+pub fn handle_joystick_button(
+    world: &mut World,
+    timestamp: u64,
+    which: u32,
+    button: u8,
+    pressed: bool,
+) {
+    let Some(joystick) = world.resource::<InputDevices>().joystick(which) else {
+        return;
+    };
+    if let Some(mut state) = world.get_mut::<JoystickState>(joystick)
+        && let Some(button) = state.buttons.get_mut(button as usize)
+    {
+        *button = pressed;
+    }
+    world.send_event(JoystickButtonEvent {
+        joystick,
+        button,
+        state: if pressed {
+            ButtonState::Pressed
+        } else {
+            ButtonState::Released
+        },
+        timestamp,
+    });
+}
+
+pub fn handle_joystick_hat(
+    world: &mut World,
+    timestamp: u64,
+    which: u32,
+    hat: u8,
+    state: HatState,
+) {
+    let Some(joystick) = world.resource::<InputDevices>().joystick(which) else {
+        return;
+    };
+    let position = JoystickHat::from(state);
+    if let Some(mut state) = world.get_mut::<JoystickState>(joystick)
+        && let Some(hat) = state.hats.get_mut(hat as usize)
+    {
+        *hat = position;
+    }
+    world.send_event(JoystickHatEvent {
+        joystick,
+        hat,
+        position,
+        timestamp,
+    });
+}
+
+pub fn handle_joystick_ball(
+    world: &mut World,
+    timestamp: u64,
+    which: u32,
+    ball: u8,
+    xrel: i16,
+    yrel: i16,
+) {
+    let Some(joystick) = world.resource::<InputDevices>().joystick(which) else {
+        return;
+    };
+    world.send_event(JoystickBallEvent {
+        joystick,
+        ball,
+        delta: Vec2::new(xrel as f32, yrel as f32),
+        timestamp,
+    });
+}
+
+fn axis_value(value: i16) -> f32 {
+    (value as f32 / i16::MAX as f32).max(-1.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_input::ButtonState;
+    use bevy_math::Vec2;
+    use sdl3::sys::{
+        events::{SDL_EVENT_JOYSTICK_BALL_MOTION, SDL_Event, SDL_JoyBallEvent},
+        joystick::{
+            SDL_GetJoystickFromID, SDL_GetJoystickGUIDForID, SDL_HAT_UP,
+            SDL_JOYSTICK_TYPE_FLIGHT_STICK, SDL_SetJoystickVirtualAxis,
+            SDL_SetJoystickVirtualButton, SDL_SetJoystickVirtualHat, SDL_VirtualJoystickDesc,
+        },
+    };
+
+    use super::{
+        JoystickAxisEvent, JoystickBallEvent, JoystickButtonEvent, JoystickHat, JoystickHatEvent,
+        JoystickInfo, JoystickState, SdlJoystick,
+    };
+    use crate::{
+        AddGamepadMappings, GamepadMappings, SdlGamepad,
+        devices::{InputDeviceEvent, InputDevices},
+        test_app::TestApp,
+    };
+
+    // SDL's virtual driver doesn't report trackballs, so ball motion is pushed as an event
+    fn flight_stick() -> SDL_VirtualJoystickDesc {
+        SDL_VirtualJoystickDesc {
+            r#type: SDL_JOYSTICK_TYPE_FLIGHT_STICK.0 as u16,
+            naxes: 2,
+            nbuttons: 2,
+            nhats: 1,
+            name: c"flight stick".as_ptr(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn virtual_joystick() {
+        let mut app = TestApp::new(&[]);
+        let id = app.attach_virtual_joystick(&flight_stick());
+        app.update();
+
+        let joystick = app
+            .world()
+            .resource::<InputDevices>()
+            .joystick(id)
+            .expect("virtual joystick was not added");
+        assert_eq!(
+            app.take_events::<InputDeviceEvent>(),
+            [InputDeviceEvent::JoystickAdded(joystick)]
+        );
+        assert_eq!(
+            app.world().get::<SdlJoystick>(joystick),
+            Some(&SdlJoystick { id })
+        );
+        let info = app.world().get::<JoystickInfo>(joystick).unwrap();
+        assert_eq!(info.name, "flight stick");
+        assert_eq!(
+            (
+                info.num_axes,
+                info.num_buttons,
+                info.num_hats,
+                info.num_balls
+            ),
+            (2, 2, 1, 0)
+        );
+        assert_eq!(app.world().resource::<InputDevices>().gamepad(id), None);
+
+        // SAFETY: called on the thread that initialized SDL, the joystick is open
+        unsafe {
+            let sdl_joystick = SDL_GetJoystickFromID(id);
+            SDL_SetJoystickVirtualAxis(sdl_joystick, 1, i16::MIN);
+            SDL_SetJoystickVirtualButton(sdl_joystick, 1, true);
+            SDL_SetJoystickVirtualHat(sdl_joystick, 0, SDL_HAT_UP);
+        }
+        app.push_event(SDL_Event {
+            jball: SDL_JoyBallEvent {
+                r#type: SDL_EVENT_JOYSTICK_BALL_MOTION,
+                which: id,
+                ball: 0,
+                xrel: 3,
+                yrel: -2,
+                ..Default::default()
+            },
+        });
+        app.update();
+
+        // SDL can report the initial value of an axis before its first motion
+        let axes = app.take_events::<JoystickAxisEvent>();
+        let last = axes.last().unwrap();
+        assert_eq!((last.joystick, last.axis, last.value), (joystick, 1, -1.0));
+        let buttons = app.take_events::<JoystickButtonEvent>();
+        assert_eq!(buttons.len(), 1);
+        assert_eq!(
+            (buttons[0].joystick, buttons[0].button, buttons[0].state),
+            (joystick, 1, ButtonState::Pressed)
+        );
+        let hats = app.take_events::<JoystickHatEvent>();
+        assert_eq!(hats.len(), 1);
+        assert_eq!(
+            (hats[0].joystick, hats[0].hat, hats[0].position),
+            (joystick, 0, JoystickHat::Up)
+        );
+        let balls = app.take_events::<JoystickBallEvent>();
+        assert_eq!(balls.len(), 1);
+        assert_eq!(
+            (balls[0].joystick, balls[0].ball, balls[0].delta),
+            (joystick, 0, Vec2::new(3.0, -2.0))
+        );
+
+        let state = app.world().get::<JoystickState>(joystick).unwrap();
+        assert_eq!(state.axes(), [0.0, -1.0]);
+        assert_eq!(state.buttons(), [false, true]);
+        assert_eq!(state.hats(), [JoystickHat::Up]);
+
+        app.detach_virtual_joystick(id);
+        app.update();
+        assert_eq!(
+            app.take_events::<InputDeviceEvent>(),
+            [InputDeviceEvent::JoystickRemoved(joystick)]
+        );
+        assert_eq!(app.world().resource::<InputDevices>().joystick(id), None);
+        assert!(app.world().get_entity(joystick).is_err());
+    }
+
+    #[test]
+    fn mapped_joystick_becomes_gamepad() {
+        let mut app = TestApp::new(&[]);
+        let id = app.attach_virtual_joystick(&flight_stick());
+        app.update();
+        let joystick = app
+            .world()
+            .resource::<InputDevices>()
+            .joystick(id)
+            .expect("virtual joystick was not added");
+        app.take_events::<InputDeviceEvent>();
+
+        let mut guid = [0; 33];
+        // SAFETY: called on the thread that initialized SDL, the buffer fits a GUID string
+        let guid = unsafe {
+            sdl3::sys::guid::SDL_GUIDToString(
+                SDL_GetJoystickGUIDForID(id),
+                guid.as_mut_ptr(),
+                guid.len() as i32,
+            );
+            std::ffi::CStr::from_ptr(guid.as_ptr())
+                .to_string_lossy()
+                .into_owned()
+        };
+        app.world_mut()
+            .send_event(AddGamepadMappings(GamepadMappings::Mappings(format!(
+                "{guid},Mapped Flight Stick,a:b0,b:b1,leftx:a0,lefty:a1,platform:{},",
+                sdl3::get_platform()
+            ))));
+        // the mapping is added at the end of the frame and the gamepad connects in the next one
+        app.update();
+        app.update();
+
+        assert_eq!(
+            app.take_events::<InputDeviceEvent>(),
+            [InputDeviceEvent::JoystickRemoved(joystick)]
+        );
+        assert_eq!(app.world().resource::<InputDevices>().joystick(id), None);
+        assert!(app.world().get_entity(joystick).is_err());
+        let gamepad = app
+            .world()
+            .resource::<InputDevices>()
+            .gamepad(id)
+            .expect("mapped joystick was not added as a gamepad");
+        assert_eq!(
+            app.world().get::<SdlGamepad>(gamepad),
+            Some(&SdlGamepad { id })
+        );
+    }
+}
@@ -1,33 +1,333 @@
-use bevy_ecs::world::World;
+use std::collections::HashMap;
+
+use bevy_ecs::{
+    change_detection::{DetectChanges, DetectChangesMut},
+    entity::Entity,
+    event::Event,
+    resource::Resource,
+    system::{Res, ResMut},
+    world::World,
+};
 use bevy_input::{
-    ButtonState,
+    ButtonInput, ButtonState,
     keyboard::{Key as BevyKey, KeyCode as BevyKeyCode, KeyboardInput, NativeKey, NativeKeyCode},
 };
-use sdl3::keyboard::{Keycode as SdlKeycode, Mod, Scancode as SdlScancode};
+use sdl3::{
+    event::Event as SdlEvent,
+    keyboard::{Keycode as SdlKeycode, Mod, Scancode as SdlScancode},
+    sys::keycode::{
+        SDL_KMOD_ALT, SDL_KMOD_CAPS, SDL_KMOD_CTRL, SDL_KMOD_GUI, SDL_KMOD_MODE, SDL_KMOD_NUM,
+        SDL_KMOD_SCROLL, SDL_KMOD_SHIFT,
+    },
+};
+
+use crate::{
+    SDL_CONTEXT, SdlContext,
+    devices::{InputDevices, sdl_name},
+    non_send_marker::NonSendMarker,
+    runner::PolledEvent,
+    timestamp::{LastInputTimestamps, send_timestamped},
+};
 
-use crate::{SDL_CONTEXT, SdlContext};
+/// A [`KeyboardInput`] with the keyboard it came from.
+///
+/// Sent after the [`KeyboardInput`] it wraps.
+#[derive(Event, Debug, Clone)]
+pub struct SdlKeyboardInput {
+    pub input: KeyboardInput,
+    /// The SDL id of the keyboard. Keys from a virtual keyboard and keys that were already held
+    /// when a window gained focus have an id of 0.
+    pub keyboard: u32,
+    /// The [`SdlKeyboard`](crate::SdlKeyboard) entity of the keyboard.
+    pub device: Option<Entity>,
+}
 
+#[allow(clippy::too_many_arguments)]
 pub fn handle_keyboard_events(
     world: &mut World,
     button_state: ButtonState,
-    _timestamp: u64,
+    timestamp: u64,
     window_id: u32,
     keycode: Option<SdlKeycode>,
     scancode: Option<SdlScancode>,
     keymod: Mod,
     repeat: bool,
-    _which: u32,
+    which: u32,
     _raw: u16,
 ) {
-    let window = SDL_CONTEXT.with_borrow(SdlContext::get_window_entity(window_id));
-    world.send_event(KeyboardInput {
+    world
+        .resource_mut::<LastInputTimestamps>()
+        .keyboards
+        .insert(which, timestamp);
+    let Some(window) = SDL_CONTEXT.with_borrow(SdlContext::get_window_entity(window_id)) else {
+        // bevy keyboard input always belongs to a window
+        return;
+    };
+    let input = keyboard_input(scancode, keycode, keymod, button_state, repeat, window);
+    send_timestamped(world, timestamp, input.clone());
+    let device = world.resource::<InputDevices>().keyboard(which);
+    world.send_event(SdlKeyboardInput {
+        input,
+        keyboard: which,
+        device,
+    });
+}
+
+fn keyboard_input(
+    scancode: Option<SdlScancode>,
+    keycode: Option<SdlKeycode>,
+    keymod: Mod,
+    state: ButtonState,
+    repeat: bool,
+    window: Entity,
+) -> KeyboardInput {
+    KeyboardInput {
         key_code: convert_sdl_scancode_to_physical_key(scancode.unwrap_or(SdlScancode::Unknown)),
         logical_key: convert_sdl_keycode_to_key(keycode.unwrap_or(SdlKeycode::Unknown), keymod),
-        state: button_state,
+        state,
         text: None,
         repeat,
-        window: window.unwrap(),
+        window,
+    }
+}
+
+/// Presses the keys that are held when a window gains focus, since the presses that happened while
+/// the window wasn't focused are never reported. `pending` are the events received after the focus.
+pub fn handle_keyboard_focus_gained(
+    world: &mut World,
+    timestamp: u64,
+    window: Entity,
+    pending: &[PolledEvent],
+) {
+    let mut count = 0;
+    // SAFETY: only called on the main thread. The state is an array of `count` bools owned by SDL.
+    let state: Vec<bool> = unsafe {
+        let state = sdl3::sys::keyboard::SDL_GetKeyboardState(&mut count);
+        if state.is_null() {
+            return;
+        }
+        std::slice::from_raw_parts(state, count as usize).to_vec()
+    };
+    let held = keys_to_press_at_focus(
+        &state,
+        pending,
+        world.get_resource::<ButtonInput<BevyKeyCode>>(),
+    );
+    // SAFETY: only called on the main thread
+    let keymod = unsafe { sdl3::sys::keyboard::SDL_GetModState() };
+
+    for scancode in held {
+        // SAFETY: only called on the main thread
+        let keycode =
+            unsafe { sdl3::sys::keyboard::SDL_GetKeyFromScancode(scancode.into(), keymod, false) };
+        let input = keyboard_input(
+            Some(scancode),
+            SdlKeycode::from_i32(keycode as i32),
+            Mod::from_bits_truncate(keymod),
+            ButtonState::Pressed,
+            false,
+            window,
+        );
+        send_timestamped(world, timestamp, input.clone());
+        // SDL doesn't know which keyboard the keys are held on
+        world.send_event(SdlKeyboardInput {
+            input,
+            keyboard: 0,
+            device: None,
+        });
+    }
+}
+
+/// The keys to press when a window gains focus, from the current keyboard `state`.
+///
+/// SDL only reports the current state, which already includes the key events received after the
+/// focus. Those events report their own presses, so a key was held at the focus if the first of
+/// them releases or repeats it, or if it is held now and none of them changes it. Keys that are
+/// still `pressed`, e.g. when the focus moves between windows of the app, were already reported.
+fn keys_to_press_at_focus(
+    state: &[bool],
+    pending: &[PolledEvent],
+    pressed: Option<&ButtonInput<BevyKeyCode>>,
+) -> Vec<SdlScancode> {
+    let mut first_events = HashMap::new();
+    for polled in pending {
+        let (scancode, held) = match polled.event {
+            SdlEvent::KeyDown {
+                scancode: Some(scancode),
+                repeat,
+                ..
+            } => (scancode, repeat),
+            SdlEvent::KeyUp {
+                scancode: Some(scancode),
+                ..
+            } => (scancode, true),
+            _ => continue,
+        };
+        first_events.entry(scancode).or_insert(held);
+    }
+
+    state
+        .iter()
+        .enumerate()
+        .filter_map(|(scancode, &held)| {
+            let scancode = SdlScancode::from_i32(scancode as i32)?;
+            let held = first_events.get(&scancode).copied().unwrap_or(held);
+            let reported = || {
+                pressed.is_some_and(|pressed| {
+                    pressed.pressed(convert_sdl_scancode_to_physical_key(scancode))
+                })
+            };
+            (held && !reported()).then_some(scancode)
+        })
+        .collect()
+}
+
+const LOCK_KEYS: u16 = SDL_KMOD_CAPS | SDL_KMOD_NUM | SDL_KMOD_SCROLL;
+
+/// The state of the modifier and lock keys as SDL tracks it. Updated every frame before the app
+/// updates.
+///
+/// The lock keys can be toggled by changing `caps_lock`, `num_lock` and `scroll_lock`. This only
+/// changes the state SDL reports, it doesn't change the keyboard LEDs.
+#[derive(Resource, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct KeyboardModifiers {
+    pub shift: bool,
+    pub ctrl: bool,
+    pub alt: bool,
+    /// The Windows, Command or Super key.
+    pub gui: bool,
+    pub caps_lock: bool,
+    pub num_lock: bool,
+    pub scroll_lock: bool,
+    /// The AltGr or Mode key.
+    pub alt_gr: bool,
+}
+
+impl KeyboardModifiers {
+    fn from_sdl(keymod: u16) -> Self {
+        Self {
+            shift: keymod & SDL_KMOD_SHIFT != 0,
+            ctrl: keymod & SDL_KMOD_CTRL != 0,
+            alt: keymod & SDL_KMOD_ALT != 0,
+            gui: keymod & SDL_KMOD_GUI != 0,
+            caps_lock: keymod & SDL_KMOD_CAPS != 0,
+            num_lock: keymod & SDL_KMOD_NUM != 0,
+            scroll_lock: keymod & SDL_KMOD_SCROLL != 0,
+            alt_gr: keymod & SDL_KMOD_MODE != 0,
+        }
+    }
+
+    fn lock_keys(&self) -> u16 {
+        let mut keymod = 0;
+        if self.caps_lock {
+            keymod |= SDL_KMOD_CAPS;
+        }
+        if self.num_lock {
+            keymod |= SDL_KMOD_NUM;
+        }
+        if self.scroll_lock {
+            keymod |= SDL_KMOD_SCROLL;
+        }
+        keymod
+    }
+}
+
+pub fn update_keyboard_modifiers(world: &mut World) {
+    // SAFETY: only called on the main thread
+    let keymod = unsafe { sdl3::sys::keyboard::SDL_GetModState() };
+    world
+        .resource_mut::<KeyboardModifiers>()
+        .set_if_neq(KeyboardModifiers::from_sdl(keymod));
+}
+
+/// system to set the lock keys when [`KeyboardModifiers`] is changed
+pub fn set_lock_keys(
+    modifiers: Res<KeyboardModifiers>,
+    // the keyboard state needs to be set on the main thread
+    _non_send: NonSendMarker,
+) {
+    if !modifiers.is_changed() {
+        return;
+    }
+    // SAFETY: only called on the main thread
+    unsafe {
+        let keymod = sdl3::sys::keyboard::SDL_GetModState();
+        if keymod & LOCK_KEYS != modifiers.lock_keys() {
+            sdl3::sys::keyboard::SDL_SetModState(keymod & !LOCK_KEYS | modifiers.lock_keys());
+        }
+    }
+}
+
+/// Sent when the keyboard layout changes, e.g. when the user switches the input language.
+/// [`KeyboardLayout`] is already updated when this is sent.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct KeymapChanged;
+
+/// The labels of the keys in the current keyboard layout.
+///
+/// Use this to show which key a [`KeyCode`](BevyKeyCode) is bound to, e.g. `KeyCode::KeyW` is
+/// labeled "Z" on an AZERTY keyboard.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct KeyboardLayout {
+    /// Labels keyed by SDL scancode.
+    labels: HashMap<i32, String>,
+    /// Whether the labels were read from SDL. Without a keyboard, e.g. with the dummy video
+    /// driver, no key has a label.
+    loaded: bool,
+}
+
+impl KeyboardLayout {
+    /// The label of the key at the position of `key_code` in the current layout, as SDL names it.
+    pub fn label(&self, key_code: BevyKeyCode) -> Option<&str> {
+        let scancode = convert_physical_key_to_sdl_scancode(key_code)?;
+        self.labels.get(&scancode.to_i32()).map(String::as_str)
+    }
+
+    /// Read the labels of all keys from SDL. Has to be called on the main thread.
+    fn load(&mut self) {
+        self.loaded = true;
+        self.labels.clear();
+        for scancode in 0..sdl3::sys::scancode::SDL_SCANCODE_COUNT.0 {
+            // SAFETY: only called on the main thread, the name is copied before the next call
+            let label = unsafe {
+                let keycode = sdl3::sys::keyboard::SDL_GetKeyFromScancode(
+                    sdl3::sys::scancode::SDL_Scancode(scancode),
+                    0,
+                    false,
+                );
+                if keycode == 0 {
+                    continue;
+                }
+                sdl_name(sdl3::sys::keyboard::SDL_GetKeyName(keycode))
+            };
+            if !label.is_empty() {
+                self.labels.insert(scancode, label);
+            }
+        }
+    }
+}
+
+pub fn handle_keymap_changed(world: &mut World) {
+    world.resource_mut::<KeyboardLayout>().load();
+    world.send_event(KeymapChanged);
+}
+
+/// system to load the [`KeyboardLayout`] once SDL knows the layout, which is after the first
+/// window is created
+pub fn load_keyboard_layout(
+    mut layout: ResMut<KeyboardLayout>,
+    // the layout needs to be read on the main thread
+    _non_send: NonSendMarker,
+) {
+    let has_window = SDL_CONTEXT.with_borrow(|context| {
+        context
+            .as_ref()
+            .is_some_and(|context| !context.windows.entity_to_winit.is_empty())
     });
+    // later changes are loaded when SDL reports them
+    if !layout.loaded && has_window {
+        layout.load();
+    }
 }
 
 // disable formatting so we can place multiple match statements on the same line and reduce the height
@@ -127,6 +427,76 @@ fn convert_sdl_scancode_to_physical_key(scancode: SdlScancode) ->  BevyKeyCode {
     }
 }
 
+// disable formatting so we can place multiple match statements on the same line and reduce the height
+#[rustfmt::skip]
+fn convert_physical_key_to_sdl_scancode(key_code: BevyKeyCode) -> Option<SdlScancode> {
+    use BevyKeyCode::*;
+    let scancode = match key_code {
+        // alphas
+        KeyA => SdlScancode::A, KeyB => SdlScancode::B, KeyC => SdlScancode::C, KeyD => SdlScancode::D, KeyE => SdlScancode::E,
+        KeyF => SdlScancode::F, KeyG => SdlScancode::G, KeyH => SdlScancode::H, KeyI => SdlScancode::I, KeyJ => SdlScancode::J,
+        KeyK => SdlScancode::K, KeyL => SdlScancode::L, KeyM => SdlScancode::M, KeyN => SdlScancode::N, KeyO => SdlScancode::O,
+        KeyP => SdlScancode::P, KeyQ => SdlScancode::Q, KeyR => SdlScancode::R, KeyS => SdlScancode::S, KeyT => SdlScancode::T,
+        KeyU => SdlScancode::U, KeyV => SdlScancode::V, KeyW => SdlScancode::W, KeyX => SdlScancode::X, KeyY => SdlScancode::Y,
+        KeyZ => SdlScancode::Z,
+        // numerals
+        Digit1 => SdlScancode::_1, Digit2 => SdlScancode::_2, Digit3 => SdlScancode::_3, Digit4 => SdlScancode::_4,
+        Digit5 => SdlScancode::_5, Digit6 => SdlScancode::_6, Digit7 => SdlScancode::_7, Digit8 => SdlScancode::_8,
+        Digit9 => SdlScancode::_9, Digit0 => SdlScancode::_0,
+        // whitespace
+        Enter => SdlScancode::Return, Escape => SdlScancode::Escape, Backspace => SdlScancode::Backspace,
+        Tab => SdlScancode::Tab, Space => SdlScancode::Space,
+        // punctuation
+        Minus => SdlScancode::Minus, Equal => SdlScancode::Equals, BracketLeft => SdlScancode::LeftBracket,
+        BracketRight => SdlScancode::RightBracket, Backslash => SdlScancode::Backslash,
+        Semicolon => SdlScancode::Semicolon, Quote => SdlScancode::Apostrophe, Backquote => SdlScancode::Grave,
+        Comma => SdlScancode::Comma, Period => SdlScancode::Period, Slash => SdlScancode::Slash,
+        CapsLock => SdlScancode::CapsLock,
+        // function keys
+        F1 => SdlScancode::F1, F2 => SdlScancode::F2, F3 => SdlScancode::F3, F4 => SdlScancode::F4, F5 => SdlScancode::F5,
+        F6 => SdlScancode::F6, F7 => SdlScancode::F7, F8 => SdlScancode::F8, F9 => SdlScancode::F9, F10 => SdlScancode::F10,
+        F11 => SdlScancode::F11, F12 => SdlScancode::F12, F13 => SdlScancode::F13, F14 => SdlScancode::F14, F15 => SdlScancode::F15,
+        F16 => SdlScancode::F16, F17 => SdlScancode::F17, F18 => SdlScancode::F18, F19 => SdlScancode::F19, F20 => SdlScancode::F20,
+        F21 => SdlScancode::F21, F22 => SdlScancode::F22, F23 => SdlScancode::F23, F24 => SdlScancode::F24,
+        // Navigation
+        PrintScreen => SdlScancode::PrintScreen, ScrollLock => SdlScancode::ScrollLock, Pause => SdlScancode::Pause,
+        Insert => SdlScancode::Insert, Home => SdlScancode::Home, PageUp => SdlScancode::PageUp,
+        Delete => SdlScancode::Delete, End => SdlScancode::End, PageDown => SdlScancode::PageDown,
+        ArrowRight => SdlScancode::Right, ArrowLeft => SdlScancode::Left, ArrowDown => SdlScancode::Down, ArrowUp => SdlScancode::Up,
+        // Numpad
+        NumLock => SdlScancode::NumLockClear,
+        NumpadDivide => SdlScancode::KpDivide, NumpadMultiply => SdlScancode::KpMultiply, NumpadSubtract => SdlScancode::KpMinus,
+        NumpadAdd => SdlScancode::KpPlus, NumpadEnter => SdlScancode::KpEnter, NumpadDecimal => SdlScancode::KpPeriod,
+        Numpad1 => SdlScancode::Kp1, Numpad2 => SdlScancode::Kp2, Numpad3 => SdlScancode::Kp3, Numpad4 => SdlScancode::Kp4,
+        Numpad5 => SdlScancode::Kp5, Numpad6 => SdlScancode::Kp6, Numpad7 => SdlScancode::Kp7, Numpad8 => SdlScancode::Kp8,
+        Numpad9 => SdlScancode::Kp9, Numpad0 => SdlScancode::Kp0,
+        NumpadComma => SdlScancode::KpComma, NumpadBackspace => SdlScancode::KpBackspace, NumpadMemoryStore => SdlScancode::KpMemStore,
+        NumpadMemoryRecall => SdlScancode::KpMemRecall, NumpadMemoryClear => SdlScancode::KpMemClear, NumpadMemoryAdd => SdlScancode::KpMemAdd,
+        NumpadMemorySubtract => SdlScancode::KpMemSubtract, NumpadEqual => SdlScancode::KpEquals,
+        // Modifiers
+        ControlLeft => SdlScancode::LCtrl, ShiftLeft => SdlScancode::LShift, AltLeft => SdlScancode::LAlt, SuperLeft => SdlScancode::LGui,
+        ControlRight => SdlScancode::RCtrl, ShiftRight => SdlScancode::RShift, AltRight => SdlScancode::RAlt, SuperRight => SdlScancode::RGui,
+        // Media
+        MediaTrackNext => SdlScancode::MediaNextTrack, MediaTrackPrevious => SdlScancode::MediaPreviousTrack,
+        MediaStop => SdlScancode::MediaStop, MediaPlayPause => SdlScancode::MediaPlayPause, MediaSelect => SdlScancode::MediaSelect,
+        AudioVolumeMute => SdlScancode::Mute, AudioVolumeUp => SdlScancode::VolumeUp, AudioVolumeDown => SdlScancode::VolumeDown,
+        // Browser
+        BrowserSearch => SdlScancode::AcSearch, BrowserHome => SdlScancode::AcHome, BrowserBack => SdlScancode::AcBack,
+        BrowserForward => SdlScancode::AcForward, BrowserStop => SdlScancode::AcStop, BrowserRefresh => SdlScancode::AcRefresh,
+        BrowserFavorites => SdlScancode::AcBookmarks,
+        // Other
+        IntlBackslash => SdlScancode::NonUsBackslash,
+        Power => SdlScancode::Power, Help => SdlScancode::Help, ContextMenu => SdlScancode::Menu, Select => SdlScancode::Select,
+        Again => SdlScancode::Again, Undo => SdlScancode::Undo,
+        Cut => SdlScancode::Cut, Copy => SdlScancode::Copy, Paste => SdlScancode::Paste, Find => SdlScancode::Find,
+        Lang1 => SdlScancode::Lang1, Lang2 => SdlScancode::Lang2, Lang3 => SdlScancode::Lang3,
+        Lang4 => SdlScancode::Lang4, Lang5 => SdlScancode::Lang5,
+        Sleep => SdlScancode::Sleep, WakeUp => SdlScancode::Wake,
+        _ => return None,
+    };
+    Some(scancode)
+}
+
 // disable formatting so we can place multiple match statements on the same line and reduce the height
 #[rustfmt::skip]
 fn convert_sdl_keycode_to_key(keycode: SdlKeycode, modifier: Mod) -> BevyKey {
@@ -254,3 +624,78 @@ fn convert_sdl_keycode_to_key(keycode: SdlKeycode, modifier: Mod) -> BevyKey {
             SdlKeycode::Call | SdlKeycode::EndCall, _) => todo!(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy_input::{ButtonInput, keyboard::KeyCode};
+    use sdl3::{
+        event::Event as SdlEvent,
+        keyboard::{Mod, Scancode},
+    };
+
+    use super::keys_to_press_at_focus;
+    use crate::runner::PolledEvent;
+
+    fn key(scancode: Scancode, pressed: bool, repeat: bool) -> PolledEvent {
+        let event = if pressed {
+            SdlEvent::KeyDown {
+                timestamp: 0,
+                window_id: 1,
+                keycode: None,
+                scancode: Some(scancode),
+                keymod: Mod::NOMOD,
+                repeat,
+                which: 1,
+                raw: scancode as u16,
+            }
+        } else {
+            SdlEvent::KeyUp {
+                timestamp: 0,
+                window_id: 1,
+                keycode: None,
+                scancode: Some(scancode),
+                keymod: Mod::NOMOD,
+                repeat,
+                which: 1,
+                raw: scancode as u16,
+            }
+        };
+        event.into()
+    }
+
+    fn state(held: &[Scancode]) -> Vec<bool> {
+        let mut state = vec![false; 512];
+        for &scancode in held {
+            state[scancode as usize] = true;
+        }
+        state
+    }
+
+    #[test]
+    fn presses_keys_held_at_focus() {
+        let state = state(&[Scancode::A, Scancode::B, Scancode::C]);
+        let pending = [
+            // pressed after the focus, the key event reports it
+            key(Scancode::B, true, false),
+            // held at the focus, released after it
+            key(Scancode::D, false, false),
+            // held at the focus and repeating
+            key(Scancode::C, true, true),
+        ];
+        assert_eq!(
+            keys_to_press_at_focus(&state, &pending, None),
+            [Scancode::A, Scancode::C, Scancode::D]
+        );
+    }
+
+    #[test]
+    fn skips_keys_already_pressed() {
+        let state = state(&[Scancode::A, Scancode::B]);
+        let mut pressed = ButtonInput::<KeyCode>::default();
+        pressed.press(KeyCode::KeyA);
+        assert_eq!(
+            keys_to_press_at_focus(&state, &[], Some(&pressed)),
+            [Scancode::B]
+        );
+    }
+}
@@ -1,378 +1,274 @@
+mod devices;
+mod gamepad;
+mod gamepad_mappings;
+mod haptic;
+mod joystick;
 mod keyboard;
 mod mouse;
 mod non_send_marker;
+mod power;
+mod proxy;
+mod raw_event;
+mod recording;
+mod runner;
+mod sensor;
+#[cfg(test)]
+mod test_app;
+mod timestamp;
+mod virtual_gamepad;
 mod window;
 
-use std::cell::RefCell;
+use std::{cell::RefCell, collections::HashMap, ffi::c_int};
 
-use bevy_app::{App, AppExit, Last, Plugin, PluginsState};
-use bevy_ecs::entity::Entity;
-use bevy_input::ButtonState;
-use bevy_window::WindowEvent;
-use sdl3::{Sdl, event::Event as SdlEvent};
+use bevy_app::{Last, Plugin, Startup};
+use bevy_ecs::{entity::Entity, resource::Resource, schedule::IntoScheduleConfigs};
+use bevy_input::{
+    gamepad::RawGamepadEvent,
+    keyboard::KeyboardInput,
+    mouse::{MouseButtonInput, MouseMotion, MouseWheel},
+};
+use bevy_window::CursorMoved;
+use sdl3::{
+    GamepadSubsystem, HapticSubsystem, JoystickSubsystem, Sdl, SensorSubsystem,
+    gamepad::Gamepad,
+    joystick::Joystick,
+    sys::{haptic::SDL_Haptic, sensor::SDL_Sensor},
+};
+
+use tracing::warn;
 
 use crate::{
-    keyboard::handle_keyboard_events,
-    mouse::{handle_mouse_button, handle_mouse_motion, handle_mouse_wheel},
-    window::{Sdl3Windows, create_windows, handle_window_events},
+    gamepad::{rumble_gamepads, update_gamepads},
+    gamepad_mappings::{add_gamepad_mappings, add_startup_gamepad_mappings},
+    haptic::update_haptic_effects,
+    keyboard::{load_keyboard_layout, set_lock_keys},
+    mouse::{update_mouse_capture, warp_cursor_global},
+    power::update_system_power,
+    proxy::UserEventHandler,
+    runner::sdl3_runner,
+    sensor::{spawn_sensors, update_sensors},
+    virtual_gamepad::update_virtual_gamepads,
+    window::{Sdl3Windows, create_windows, update_windows},
 };
 
+pub use crate::{
+    devices::{InputDeviceEvent, InputDevices, SdlKeyboard, SdlMouse},
+    gamepad::{
+        GamepadConnectionState, GamepadInfo, GamepadLed, GamepadPlayerIndex, GamepadSensorEvent,
+        GamepadSensorType, GamepadSensors, GamepadTouchpadEvent, GamepadTriggerRumbleRequest,
+        SdlGamepad,
+    },
+    gamepad_mappings::{AddGamepadMappings, GamepadMappings, GamepadRemapped},
+    haptic::{
+        HapticCondition, HapticDirection, HapticEffect, HapticEffectKind, HapticEnvelope,
+        HapticInfo, HapticRequest, HapticWaveform, SdlHaptic,
+    },
+    joystick::{
+        JoystickAxisEvent, JoystickBallEvent, JoystickButtonEvent, JoystickHat, JoystickHatEvent,
+        JoystickInfo, JoystickState, SdlJoystick,
+    },
+    keyboard::{KeyboardLayout, KeyboardModifiers, KeymapChanged, SdlKeyboardInput},
+    mouse::{CaptureMouse, SdlMouseButtonInput, SdlMouseMotion, SdlMouseWheel, WarpCursorGlobal},
+    power::{Battery, PowerState, SystemPower},
+    proxy::{SdlEventProxy, SdlEventProxyPlugin},
+    raw_event::SdlRawEvent,
+    recording::{InputRecorder, InputRecording, RecordedEvent},
+    sensor::{SdlSensor, SensorEnabled, SensorEvent, SensorReading, SensorType},
+    timestamp::{LastInputTimestamps, Timestamped, sdl_ticks_ns},
+    virtual_gamepad::{VirtualGamepad, VirtualGamepadInput},
+};
+
+#[cfg(feature = "bevy_asset")]
+pub use crate::gamepad_mappings::{GamepadMappingsAsset, GamepadMappingsLoader};
+
 pub struct Sdl3Plugin;
 impl Plugin for Sdl3Plugin {
     fn build(&self, app: &mut bevy_app::App) {
         SdlContext::init();
+        app.init_resource::<Sdl3Settings>()
+            .init_resource::<LastInputTimestamps>()
+            .init_resource::<InputRecorder>()
+            .init_resource::<InputDevices>()
+            .init_resource::<KeyboardLayout>()
+            .init_resource::<KeyboardModifiers>()
+            .init_resource::<SystemPower>()
+            .add_event::<SdlRawEvent>()
+            .add_event::<InputDeviceEvent>()
+            .add_event::<KeymapChanged>()
+            .add_event::<GamepadTriggerRumbleRequest>()
+            .add_event::<GamepadSensorEvent>()
+            .add_event::<GamepadTouchpadEvent>()
+            .add_event::<AddGamepadMappings>()
+            .add_event::<GamepadRemapped>()
+            .add_event::<JoystickAxisEvent>()
+            .add_event::<JoystickButtonEvent>()
+            .add_event::<JoystickHatEvent>()
+            .add_event::<JoystickBallEvent>()
+            .add_event::<VirtualGamepadInput>()
+            .add_event::<HapticRequest>()
+            .add_event::<SensorEvent>()
+            .add_event::<WarpCursorGlobal>()
+            .add_event::<CaptureMouse>()
+            .add_event::<SdlKeyboardInput>()
+            .add_event::<SdlMouseMotion>()
+            .add_event::<SdlMouseButtonInput>()
+            .add_event::<SdlMouseWheel>()
+            .add_event::<Timestamped<KeyboardInput>>()
+            .add_event::<Timestamped<MouseMotion>>()
+            .add_event::<Timestamped<MouseButtonInput>>()
+            .add_event::<Timestamped<MouseWheel>>()
+            .add_event::<Timestamped<CursorMoved>>()
+            .add_event::<Timestamped<RawGamepadEvent>>();
+        add_startup_gamepad_mappings(&app.world().resource::<Sdl3Settings>().gamepad_mappings);
         app.set_runner(sdl3_runner);
-        app.add_systems(Last, create_windows);
-    }
-}
-
-fn sdl3_runner(mut app: App) -> AppExit {
-    if app.plugins_state() == PluginsState::Ready {
-        app.finish();
-        app.cleanup();
-    }
-
-    let mut event_pump = SDL_CONTEXT
-        .with_borrow_mut(|sdl_context| sdl_context.as_mut().unwrap().sdl.event_pump())
-        .unwrap();
+        app.add_systems(
+            Last,
+            (
+                (create_windows, update_windows, load_keyboard_layout).chain(),
+                warp_cursor_global,
+                update_mouse_capture,
+                set_lock_keys,
+                rumble_gamepads,
+                update_gamepads,
+                add_gamepad_mappings,
+                update_system_power,
+                update_virtual_gamepads,
+                update_haptic_effects,
+                update_sensors,
+            ),
+        );
+        app.add_systems(Startup, spawn_sensors);
+        #[cfg(feature = "bevy_asset")]
+        {
+            use bevy_asset::AssetApp;
 
-    'running: loop {
-        if app.plugins_state() != PluginsState::Cleaned {
-            app.finish();
-            app.cleanup();
+            // needs to be added after the `AssetPlugin`
+            app.init_asset::<GamepadMappingsAsset>()
+                .init_asset_loader::<GamepadMappingsLoader>()
+                .add_systems(Last, gamepad_mappings::add_gamepad_mapping_assets);
         }
+    }
+}
 
-        for event in event_pump.poll_iter() {
-            match event {
-                SdlEvent::Window {
-                    timestamp,
-                    window_id,
-                    win_event,
-                } => handle_window_events(app.world_mut(), timestamp, window_id, win_event),
-                // Keyboard Events
-                SdlEvent::KeyDown {
-                    timestamp,
-                    window_id,
-                    keycode,
-                    scancode,
-                    keymod,
-                    repeat,
-                    which,
-                    raw,
-                } => handle_keyboard_events(
-                    app.world_mut(),
-                    ButtonState::Pressed,
-                    timestamp,
-                    window_id,
-                    keycode,
-                    scancode,
-                    keymod,
-                    repeat,
-                    which,
-                    raw,
-                ),
-                SdlEvent::KeyUp {
-                    timestamp,
-                    window_id,
-                    keycode,
-                    scancode,
-                    keymod,
-                    repeat,
-                    which,
-                    raw,
-                } => handle_keyboard_events(
-                    app.world_mut(),
-                    ButtonState::Released,
-                    timestamp,
-                    window_id,
-                    keycode,
-                    scancode,
-                    keymod,
-                    repeat,
-                    which,
-                    raw,
-                ),
-                // Mouse Events
-                SdlEvent::MouseMotion {
-                    timestamp: _,
-                    window_id,
-                    which: _,
-                    mousestate: _,
-                    x,
-                    y,
-                    xrel,
-                    yrel,
-                } => handle_mouse_motion(app.world_mut(), window_id, x, y, xrel, yrel),
-                SdlEvent::MouseButtonDown {
-                    timestamp: _,
-                    window_id,
-                    which: _,
-                    mouse_btn,
-                    clicks: _,
-                    x: _,
-                    y: _,
-                } => handle_mouse_button(
-                    app.world_mut(),
-                    window_id,
-                    mouse_btn.into(),
-                    ButtonState::Pressed,
-                ),
-                SdlEvent::MouseButtonUp {
-                    timestamp: _,
-                    window_id,
-                    which: _,
-                    mouse_btn,
-                    clicks: _,
-                    x: _,
-                    y: _,
-                } => handle_mouse_button(
-                    app.world_mut(),
-                    window_id,
-                    mouse_btn.into(),
-                    ButtonState::Released,
-                ),
-                SdlEvent::MouseWheel {
-                    timestamp: _,
-                    window_id,
-                    which: _,
-                    x,
-                    y,
-                    direction,
-                    // position on window
-                    mouse_x: _,
-                    mouse_y: _,
-                } => handle_mouse_wheel(app.world_mut(), window_id, x, y, direction),
-                SdlEvent::Quit { .. } => {
-                    break 'running;
-                }
-                // TODO: we may need to do more with AppLifecyle to match the winit behavior
-                SdlEvent::AppWillEnterBackground { timestamp: _ } => {
-                    app.world_mut().send_event(WindowEvent::AppLifecycle(
-                        bevy_window::AppLifecycle::WillSuspend,
-                    ));
-                }
-                SdlEvent::AppDidEnterBackground { timestamp: _ } => {
-                    app.world_mut().send_event(WindowEvent::AppLifecycle(
-                        bevy_window::AppLifecycle::Suspended,
-                    ));
-                }
-                SdlEvent::AppWillEnterForeground { timestamp: _ } => {
-                    app.world_mut().send_event(WindowEvent::AppLifecycle(
-                        bevy_window::AppLifecycle::WillResume,
-                    ));
-                }
-                SdlEvent::AppDidEnterForeground { timestamp: _ } => {
-                    app.world_mut().send_event(WindowEvent::AppLifecycle(
-                        bevy_window::AppLifecycle::Running,
-                    ));
-                }
-                // SdlEvent::AppTerminating { timestamp } => todo!(),
-                // SdlEvent::AppLowMemory { timestamp } => todo!(),
-                // SdlEvent::TextEditing {
-                //     timestamp,
-                //     window_id,
-                //     text,
-                //     start,
-                //     length,
-                // } => todo!(),
-                // SdlEvent::TextInput {
-                //     timestamp,
-                //     window_id,
-                //     text,
-                // } => todo!(),
-                // SdlEvent::JoyAxisMotion {
-                //     timestamp,
-                //     which,
-                //     axis_idx,
-                //     value,
-                // } => todo!(),
-                // SdlEvent::JoyHatMotion {
-                //     timestamp,
-                //     which,
-                //     hat_idx,
-                //     state,
-                // } => todo!(),
-                // SdlEvent::JoyButtonDown {
-                //     timestamp,
-                //     which,
-                //     button_idx,
-                // } => todo!(),
-                // SdlEvent::JoyButtonUp {
-                //     timestamp,
-                //     which,
-                //     button_idx,
-                // } => todo!(),
-                // SdlEvent::JoyDeviceAdded { timestamp, which } => todo!(),
-                // SdlEvent::JoyDeviceRemoved { timestamp, which } => todo!(),
-                // SdlEvent::ControllerAxisMotion {
-                //     timestamp,
-                //     which,
-                //     axis,
-                //     value,
-                // } => todo!(),
-                // SdlEvent::ControllerButtonDown {
-                //     timestamp,
-                //     which,
-                //     button,
-                // } => todo!(),
-                // SdlEvent::ControllerButtonUp {
-                //     timestamp,
-                //     which,
-                //     button,
-                // } => todo!(),
-                // SdlEvent::ControllerDeviceAdded { timestamp, which } => todo!(),
-                // SdlEvent::ControllerDeviceRemoved { timestamp, which } => todo!(),
-                // SdlEvent::ControllerDeviceRemapped { timestamp, which } => todo!(),
-                // SdlEvent::ControllerTouchpadDown {
-                //     timestamp,
-                //     which,
-                //     touchpad,
-                //     finger,
-                //     x,
-                //     y,
-                //     pressure,
-                // } => todo!(),
-                // SdlEvent::ControllerTouchpadMotion {
-                //     timestamp,
-                //     which,
-                //     touchpad,
-                //     finger,
-                //     x,
-                //     y,
-                //     pressure,
-                // } => todo!(),
-                // SdlEvent::ControllerTouchpadUp {
-                //     timestamp,
-                //     which,
-                //     touchpad,
-                //     finger,
-                //     x,
-                //     y,
-                //     pressure,
-                // } => todo!(),
-                // SdlEvent::FingerDown {
-                //     timestamp,
-                //     touch_id,
-                //     finger_id,
-                //     x,
-                //     y,
-                //     dx,
-                //     dy,
-                //     pressure,
-                // } => todo!(),
-                // SdlEvent::FingerUp {
-                //     timestamp,
-                //     touch_id,
-                //     finger_id,
-                //     x,
-                //     y,
-                //     dx,
-                //     dy,
-                //     pressure,
-                // } => todo!(),
-                // SdlEvent::FingerMotion {
-                //     timestamp,
-                //     touch_id,
-                //     finger_id,
-                //     x,
-                //     y,
-                //     dx,
-                //     dy,
-                //     pressure,
-                // } => todo!(),
-                // SdlEvent::DollarRecord {
-                //     timestamp,
-                //     touch_id,
-                //     gesture_id,
-                //     num_fingers,
-                //     error,
-                //     x,
-                //     y,
-                // } => todo!(),
-                // SdlEvent::MultiGesture {
-                //     timestamp,
-                //     touch_id,
-                //     d_theta,
-                //     d_dist,
-                //     x,
-                //     y,
-                //     num_fingers,
-                // } => todo!(),
-                // SdlEvent::ClipboardUpdate { timestamp } => todo!(),
-                // SdlEvent::DropFile {
-                //     timestamp,
-                //     window_id,
-                //     filename,
-                // } => todo!(),
-                // SdlEvent::DropText {
-                //     timestamp,
-                //     window_id,
-                //     filename,
-                // } => todo!(),
-                // SdlEvent::DropBegin {
-                //     timestamp,
-                //     window_id,
-                // } => todo!(),
-                // SdlEvent::DropComplete {
-                //     timestamp,
-                //     window_id,
-                // } => todo!(),
-                // SdlEvent::AudioDeviceAdded {
-                //     timestamp,
-                //     which,
-                //     iscapture,
-                // } => todo!(),
-                // SdlEvent::AudioDeviceRemoved {
-                //     timestamp,
-                //     which,
-                //     iscapture,
-                // } => todo!(),
-                // SdlEvent::RenderTargetsReset { timestamp } => todo!(),
-                // SdlEvent::RenderDeviceReset { timestamp } => todo!(),
-                // SdlEvent::User {
-                //     timestamp,
-                //     window_id,
-                //     type_,
-                //     code,
-                //     data1,
-                //     data2,
-                // } => todo!(),
-                // SdlEvent::Unknown { timestamp, type_ } => todo!(),
-                // SdlEvent::Display {
-                //     timestamp,
-                //     display,
-                //     display_event,
-                // } => todo!(),
-                e => {
-                    dbg!(e);
-                }
-            }
-        }
+/// Settings for the sdl3 backend. Insert this resource before adding [`Sdl3Plugin`] to
+/// change the defaults.
+#[derive(Resource, Debug, Clone, Default)]
+pub struct Sdl3Settings {
+    /// Send every event received from SDL into the world as a [`SdlRawEvent`].
+    pub forward_raw_events: bool,
+    /// How mouse motion events are sent into the world.
+    pub mouse_motion: MouseMotionMode,
+    /// When the mouse is captured, see [`CaptureMouse`].
+    pub mouse_capture: MouseCaptureMode,
+    /// Gamepad mappings added when the plugin is built, before any gamepad is connected. See
+    /// [`AddGamepadMappings`] to add mappings later.
+    pub gamepad_mappings: Vec<GamepadMappings>,
+}
 
-        if app.plugins_state() == PluginsState::Cleaned {
-            app.update();
-        }
-    }
+/// Controls how SDL mouse motion events are turned into `MouseMotion` and `CursorMoved` events.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MouseMotionMode {
+    /// Send an event for every motion event SDL reports.
+    #[default]
+    Raw,
+    /// Merge consecutive motion events received during a frame into a single event.
+    Coalesced,
+}
 
-    AppExit::Success
+/// Controls when the mouse is captured by the focused window.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum MouseCaptureMode {
+    /// Capture the mouse while any mouse button is held, so drags that leave the window keep
+    /// reporting motion and the button release. Can still be captured manually.
+    #[default]
+    WhileDragging,
+    /// Only capture the mouse with [`CaptureMouse`].
+    Manual,
 }
 
 thread_local! {
-    static SDL_CONTEXT: RefCell<Option<SdlContext>>  = RefCell::new(None);
+    static SDL_CONTEXT: RefCell<Option<SdlContext>>  = const { RefCell::new(None) };
 }
 
 pub struct SdlContext {
     sdl: Sdl,
+    gamepad: GamepadSubsystem,
+    joystick: JoystickSubsystem,
+    /// Keeps the haptic subsystem initialized, haptic devices are used through `sdl3::sys`. `None`
+    /// if the subsystem failed to initialize.
+    haptic: Option<HapticSubsystem>,
+    /// Keeps the sensor subsystem initialized, sensors are used through `sdl3::sys`. `None` if the
+    /// subsystem failed to initialize.
+    sensor: Option<SensorSubsystem>,
     windows: Sdl3Windows,
+    user_events: HashMap<u32, UserEventHandler>,
+    /// The opened gamepads, keyed by their SDL joystick id.
+    gamepads: HashMap<u32, Gamepad>,
+    /// The opened joysticks that aren't gamepads, keyed by their SDL joystick id.
+    joysticks: HashMap<u32, Joystick>,
+    /// The opened virtual gamepads, keyed by the entity of their `VirtualGamepad`.
+    virtual_gamepads: HashMap<Entity, Joystick>,
+    /// The opened haptic devices, keyed by their SDL haptic id.
+    haptics: HashMap<u32, *mut SDL_Haptic>,
+    /// The uploaded haptic effects as haptic id and effect id, keyed by the entity of their
+    /// `HapticEffect`.
+    haptic_effects: HashMap<Entity, (u32, c_int)>,
+    /// The opened sensors, keyed by their SDL sensor id.
+    sensors: HashMap<u32, *mut SDL_Sensor>,
+}
+
+impl Drop for SdlContext {
+    fn drop(&mut self) {
+        // the payloads of proxy events are only freed when they are received
+        self.free_queued_user_events();
+        // SAFETY: only dropped on the main thread, the devices were opened by us and the
+        // subsystems are still initialized
+        unsafe {
+            for (_, haptic) in self.haptics.drain() {
+                sdl3::sys::haptic::SDL_CloseHaptic(haptic);
+            }
+            for (_, sensor) in self.sensors.drain() {
+                sdl3::sys::sensor::SDL_CloseSensor(sensor);
+            }
+        }
+    }
 }
 
 impl SdlContext {
     /// should be only called on the main thread
     fn init() {
+        // keep relative mouse motion unaccelerated, see `CursorGrabMode::Locked`
+        sdl3::hint::set("SDL_MOUSE_RELATIVE_SYSTEM_SCALE", "0");
+
+        let sdl = sdl3::init().unwrap();
+        // gamepads that are already connected are reported as added once events are polled
+        let gamepad = sdl.gamepad().unwrap();
+        let joystick = sdl.joystick().unwrap();
+        // haptics and sensors are optional, the app works without them
+        let haptic = sdl
+            .haptic()
+            .inspect_err(|err| warn!("failed to initialize haptics: {err}"))
+            .ok();
+        let sensor = sdl
+            .sensor()
+            .inspect_err(|err| warn!("failed to initialize sensors: {err}"))
+            .ok();
+
         SDL_CONTEXT.with_borrow_mut(|context| {
             *context = Some(SdlContext {
-                sdl: sdl3::init().unwrap(),
+                sdl,
+                gamepad,
+                joystick,
+                haptic,
+                sensor,
                 windows: Sdl3Windows::new(),
+                user_events: HashMap::new(),
+                gamepads: HashMap::new(),
+                joysticks: HashMap::new(),
+                virtual_gamepads: HashMap::new(),
+                haptics: HashMap::new(),
+                haptic_effects: HashMap::new(),
+                sensors: HashMap::new(),
             });
         });
     }
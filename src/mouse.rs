@@ -1,34 +1,81 @@
-use bevy_ecs::world::World;
+use bevy_ecs::{
+    change_detection::DetectChanges,
+    entity::Entity,
+    event::{Event, EventReader},
+    system::Res,
+    world::World,
+};
 use bevy_input::{
-    ButtonState,
+    ButtonInput, ButtonState,
     mouse::{
         MouseButton as BevyMouseButton, MouseButtonInput, MouseMotion, MouseScrollUnit, MouseWheel,
     },
 };
 use bevy_math::Vec2;
 use bevy_window::{CursorMoved, Window as BevyWindow};
-use sdl3::mouse::{MouseButton as SdlMouseButton, MouseWheelDirection};
+use sdl3::{
+    event::Event as SdlEvent,
+    mouse::{MouseButton as SdlMouseButton, MouseWheelDirection},
+    sys::{
+        mouse::{
+            SDL_BUTTON_LEFT, SDL_BUTTON_MIDDLE, SDL_BUTTON_RIGHT, SDL_BUTTON_X1, SDL_BUTTON_X2,
+            SDL_GetMouseFocus,
+        },
+        video::SDL_GetWindowID,
+    },
+};
 
-use crate::{SDL_CONTEXT, SdlContext};
+use crate::{
+    MouseCaptureMode, SDL_CONTEXT, Sdl3Settings, SdlContext,
+    devices::InputDevices,
+    non_send_marker::NonSendMarker,
+    runner::PolledEvent,
+    timestamp::{LastInputTimestamps, send_timestamped},
+};
 
+#[allow(clippy::too_many_arguments)]
 pub fn handle_mouse_motion(
     world: &mut World,
+    timestamp: u64,
     window_id: u32,
+    which: u32,
     x: f32,
     y: f32,
     xrel: f32,
     yrel: f32,
 ) {
-    let (entity, scale) = SDL_CONTEXT
-        .with_borrow(SdlContext::get_window_entity_and_scale(window_id))
-        .unwrap();
+    let physical_position = Vec2::new(x, y);
+    let window = SDL_CONTEXT.with_borrow(SdlContext::get_window_entity_and_scale(window_id));
+
+    if let Some((entity, _)) = window
+        && is_warp(entity, physical_position)
+    {
+        // the cursor position was already set when warping
+        return;
+    }
+
+    world
+        .resource_mut::<LastInputTimestamps>()
+        .mice
+        .insert(which, timestamp);
 
     // Note that this is actually sending the accumulated mouse delta unlike winit
-    world.send_event(MouseMotion {
+    let input = MouseMotion {
         delta: Vec2::new(xrel, yrel),
+    };
+    send_timestamped(world, timestamp, input);
+    let device = world.resource::<InputDevices>().mouse(which);
+    world.send_event(SdlMouseMotion {
+        input,
+        mouse: which,
+        device,
     });
 
-    let physical_position = Vec2::new(x, y);
+    // motion is still reported without a window when a grabbed window loses mouse focus, but
+    // the cursor can only be tracked over a window
+    let Some((entity, scale)) = window else {
+        return;
+    };
     let logical_position = physical_position / scale;
 
     let mut entity_mut = world.get_entity_mut(entity).unwrap();
@@ -38,58 +85,485 @@ pub fn handle_mouse_motion(
     let delta = last_position.map(|last_pos| (physical_position - last_pos) / scale);
     bevy_window.set_physical_cursor_position(Some(physical_position.into()));
 
-    world.send_event(CursorMoved {
-        window: entity,
-        position: logical_position,
-        delta,
-    });
+    send_timestamped(
+        world,
+        timestamp,
+        CursorMoved {
+            window: entity,
+            position: logical_position,
+            delta,
+        },
+    );
+}
+
+/// Records the cursor position reported by `sdl3` and checks if the motion was caused by warping
+/// the cursor to that position.
+fn is_warp(window: Entity, position: Vec2) -> bool {
+    SDL_CONTEXT.with_borrow_mut(|context| {
+        let Some(context) = context.as_mut() else {
+            return false;
+        };
+        let windows = &mut context.windows;
+        windows.cursor_positions.insert(window, position);
+
+        match windows.pending_warps.remove(&window) {
+            Some(target) => target.distance(position) < 1.0,
+            None => false,
+        }
+    })
+}
+
+/// Moves the cursor to a position on the desktop. The position is in global screen coordinates.
+///
+/// Unlike setting the cursor position of a [`Window`](BevyWindow), this will report the motion
+/// like any other cursor movement.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct WarpCursorGlobal {
+    pub position: Vec2,
+}
+
+/// system to warp the cursor for [`WarpCursorGlobal`] events
+pub fn warp_cursor_global(
+    mut events: EventReader<WarpCursorGlobal>,
+    // the cursor needs to be warped on the main thread
+    _non_send: NonSendMarker,
+) {
+    for event in events.read() {
+        // SAFETY: only called on the main thread
+        unsafe {
+            sdl3::sys::mouse::SDL_WarpMouseGlobal(event.position.x, event.position.y);
+        }
+    }
+}
+
+/// Captures the mouse, so mouse input keeps being reported to the focused window while the cursor
+/// is outside of it. Send `CaptureMouse(false)` to release the capture.
+///
+/// The window also keeps receiving [`CursorMoved`] events while captured, but
+/// `Window::cursor_position` is `None` while the cursor is outside the window. `CursorLeft` is
+/// only sent once the capture is released.
+///
+/// By default the mouse is captured automatically while a button is held, see
+/// [`MouseCaptureMode`](crate::MouseCaptureMode). The capture fails if no window has focus.
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CaptureMouse(pub bool);
+
+/// system to apply the [`MouseCaptureMode`](crate::MouseCaptureMode) and [`CaptureMouse`] events
+pub fn update_mouse_capture(
+    settings: Res<Sdl3Settings>,
+    mut events: EventReader<CaptureMouse>,
+    // the mouse needs to be captured on the main thread
+    _non_send: NonSendMarker,
+) {
+    if settings.is_changed() {
+        let auto_capture = settings.mouse_capture == MouseCaptureMode::WhileDragging;
+        sdl3::hint::set(
+            "SDL_MOUSE_AUTO_CAPTURE",
+            if auto_capture { "1" } else { "0" },
+        );
+    }
+
+    for CaptureMouse(capture) in events.read() {
+        // SAFETY: only called on the main thread
+        unsafe {
+            sdl3::sys::mouse::SDL_CaptureMouse(*capture);
+        }
+    }
 }
 
+/// A [`MouseMotion`] with the mouse it came from.
+///
+/// Sent after the [`MouseMotion`] it wraps.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SdlMouseMotion {
+    pub input: MouseMotion,
+    /// The SDL id of the mouse.
+    pub mouse: u32,
+    /// The [`SdlMouse`](crate::SdlMouse) entity of the mouse.
+    pub device: Option<Entity>,
+}
+
+/// A [`MouseButtonInput`] with the extra information SDL reports for it.
+///
+/// Sent after the [`MouseButtonInput`] it wraps.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SdlMouseButtonInput {
+    pub input: MouseButtonInput,
+    /// The number of consecutive clicks, e.g. 2 for a double click. Uses the double click time
+    /// and distance of the OS.
+    pub clicks: u8,
+    /// The logical position of the cursor in the window when the button changed state.
+    pub position: Vec2,
+    /// The SDL id of the mouse. Touch and pen input that is emulating a mouse use
+    /// `SDL_TOUCH_MOUSEID` and `SDL_PEN_MOUSEID`.
+    pub mouse: u32,
+    /// The [`SdlMouse`](crate::SdlMouse) entity of the mouse. Touch and pen input has no entity.
+    pub device: Option<Entity>,
+}
+
+/// A [`MouseWheel`] with the extra information SDL reports for it.
+///
+/// Sent after the [`MouseWheel`] it wraps.
+#[derive(Event, Debug, Clone, Copy)]
+pub struct SdlMouseWheel {
+    pub input: MouseWheel,
+    /// The logical position of the cursor in the window when the wheel was scrolled.
+    pub position: Vec2,
+    /// The SDL id of the mouse.
+    pub mouse: u32,
+    /// The [`SdlMouse`](crate::SdlMouse) entity of the mouse.
+    pub device: Option<Entity>,
+    /// True if the OS uses natural scrolling. Negate the delta to get the physical direction the
+    /// wheel was turned.
+    pub flipped: bool,
+}
+
+impl SdlMouseWheel {
+    /// The scrolled amount in the direction the wheel was physically turned, without natural
+    /// scrolling applied.
+    pub fn physical_delta(&self) -> Vec2 {
+        let delta = Vec2::new(self.input.x, self.input.y);
+        if self.flipped { -delta } else { delta }
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn handle_mouse_button(
     world: &mut World,
+    timestamp: u64,
     window_id: u32,
-    button: SdlMouseButton,
+    which: u32,
+    button: u8,
     state: ButtonState,
+    clicks: u8,
+    x: f32,
+    y: f32,
 ) {
-    let window = SDL_CONTEXT
-        .with_borrow(SdlContext::get_window_entity(window_id))
-        .unwrap();
-    world.send_event(MouseButtonInput {
+    world
+        .resource_mut::<LastInputTimestamps>()
+        .mice
+        .insert(which, timestamp);
+    let Some((window, scale)) = mouse_event_window(window_id) else {
+        return;
+    };
+
+    let input = MouseButtonInput {
         button: convert_sdl_mouse_button(button),
         state,
         window,
+    };
+    send_timestamped(world, timestamp, input);
+    let device = world.resource::<InputDevices>().mouse(which);
+    world.send_event(SdlMouseButtonInput {
+        input,
+        clicks,
+        position: Vec2::new(x, y) / scale,
+        mouse: which,
+        device,
     });
 }
 
+#[allow(clippy::too_many_arguments)]
 pub fn handle_mouse_wheel(
     world: &mut World,
+    timestamp: u64,
     window_id: u32,
+    which: u32,
     x: f32, // positive to the right and negative to the left
     y: f32, // positive away from the user and negative toward the user
-    _direction: MouseWheelDirection,
+    direction: MouseWheelDirection,
+    mouse_x: f32,
+    mouse_y: f32,
 ) {
-    // TODO: figure out how to deal with flipped mouse wheel direction
-    // TODO: get scrolled lines from sdl. not exposed by lib yet
-    let window = SDL_CONTEXT
-        .with_borrow(SdlContext::get_window_entity(window_id))
-        .unwrap();
-    world.send_event(MouseWheel {
-        unit: MouseScrollUnit::Pixel,
-        x,
-        y,
+    world
+        .resource_mut::<LastInputTimestamps>()
+        .mice
+        .insert(which, timestamp);
+    let Some((window, scale)) = mouse_event_window(window_id) else {
+        return;
+    };
+
+    let (unit, delta) = convert_sdl_mouse_wheel(x, y);
+    let input = MouseWheel {
+        unit,
+        x: delta.x,
+        y: delta.y,
         window,
+    };
+    send_timestamped(world, timestamp, input);
+    let device = world.resource::<InputDevices>().mouse(which);
+    world.send_event(SdlMouseWheel {
+        input,
+        position: Vec2::new(mouse_x, mouse_y) / scale,
+        mouse: which,
+        device,
+        flipped: direction == MouseWheelDirection::Flipped,
     });
 }
 
-pub fn convert_sdl_mouse_button(sdl_button: SdlMouseButton) -> BevyMouseButton {
-    match sdl_button {
-        // TODO: should map other mouse buttons, should bevy have an unknown state?
-        // need to add passing through to the sdl3 crate
-        SdlMouseButton::Unknown => BevyMouseButton::Other(0),
-        SdlMouseButton::Left => BevyMouseButton::Left,
-        SdlMouseButton::Middle => BevyMouseButton::Middle,
-        SdlMouseButton::Right => BevyMouseButton::Right,
-        SdlMouseButton::X1 => BevyMouseButton::Back,
-        SdlMouseButton::X2 => BevyMouseButton::Forward,
+/// The window of a mouse button or wheel event.
+///
+/// While the mouse is captured, events outside of all windows are reported without a window, so
+/// those go to the window with mouse focus, which is the window that captured the mouse.
+fn mouse_event_window(window_id: u32) -> Option<(Entity, f32)> {
+    SDL_CONTEXT
+        .with_borrow(SdlContext::get_window_entity_and_scale(window_id))
+        .or_else(|| {
+            // SAFETY: only called on the main thread, SDL_GetWindowID returns 0 without a focus
+            let focus = unsafe { SDL_GetWindowID(SDL_GetMouseFocus()) };
+            SDL_CONTEXT.with_borrow(SdlContext::get_window_entity_and_scale(focus))
+        })
+}
+
+/// SDL reports precise scrolling in wheel ticks by dividing the pixel deltas of the OS by 10 on
+/// macOS and Wayland, so this converts them back to pixels.
+const PIXELS_PER_WHEEL_TICK: f32 = 10.0;
+
+/// Converts the amount scrolled reported by SDL into a bevy scroll unit and delta.
+///
+/// Notched mouse wheels scroll in whole ticks, which are reported as lines. Precise scrolling
+/// devices like touchpads scroll in fractions of a tick, which are reported as pixels. SDL's
+/// `integer_x` and `integer_y` aren't used to tell them apart, since they include the fractions
+/// left over from earlier precise scrolling.
+///
+/// Like `bevy_winit`, the delta keeps the direction reported by the OS, so natural scrolling
+/// ([`MouseWheelDirection::Flipped`]) is already applied to it, see [`SdlMouseWheel::flipped`].
+pub fn convert_sdl_mouse_wheel(x: f32, y: f32) -> (MouseScrollUnit, Vec2) {
+    let delta = Vec2::new(x, y);
+    if delta.fract_gl() == Vec2::ZERO {
+        (MouseScrollUnit::Line, delta)
+    } else {
+        (MouseScrollUnit::Pixel, delta * PIXELS_PER_WHEEL_TICK)
+    }
+}
+
+/// Releases the pressed mouse buttons when a window loses focus, since the releases that happen
+/// while the window isn't focused are never reported.
+pub fn release_mouse_buttons(world: &mut World, timestamp: u64, window: Entity) {
+    let Some(buttons) = world.get_resource::<ButtonInput<BevyMouseButton>>() else {
+        return;
+    };
+    let pressed: Vec<_> = buttons.get_pressed().copied().collect();
+
+    for button in pressed {
+        let input = MouseButtonInput {
+            button,
+            state: ButtonState::Released,
+            window,
+        };
+        send_timestamped(world, timestamp, input);
+    }
+}
+
+/// Presses and releases mouse buttons to match the buttons SDL reports as held when a window gains
+/// focus. `pending` are the events received after the focus.
+pub fn sync_mouse_buttons(
+    world: &mut World,
+    timestamp: u64,
+    window: Entity,
+    pending: &[PolledEvent],
+) {
+    let Some(buttons) = world.get_resource::<ButtonInput<BevyMouseButton>>() else {
+        return;
+    };
+    // SAFETY: only called on the main thread
+    let held =
+        unsafe { sdl3::sys::mouse::SDL_GetMouseState(std::ptr::null_mut(), std::ptr::null_mut()) };
+    let held = buttons_held_at_focus(held, pending);
+
+    let mut inputs = Vec::new();
+    for index in 1..=u32::BITS as u8 {
+        let button = convert_sdl_mouse_button(index);
+        let is_held = held & (1 << (index - 1)) != 0;
+        let state = match (is_held, buttons.pressed(button)) {
+            (true, false) => ButtonState::Pressed,
+            (false, true) => ButtonState::Released,
+            _ => continue,
+        };
+        inputs.push(MouseButtonInput {
+            button,
+            state,
+            window,
+        });
+    }
+
+    for input in inputs {
+        send_timestamped(world, timestamp, input);
+    }
+}
+
+/// The mouse buttons that were held when a window gained focus, from the current button mask.
+///
+/// SDL only reports the current state, which already includes the button events received after
+/// the focus, e.g. the click that focused the window. Those events report their own changes, so
+/// the first change of each button is undone.
+fn buttons_held_at_focus(mut held: u32, pending: &[PolledEvent]) -> u32 {
+    let mut changed = 0;
+    for polled in pending {
+        let (button, pressed) = match polled.event {
+            SdlEvent::MouseButtonDown { mouse_btn, .. } => (mouse_btn, true),
+            SdlEvent::MouseButtonUp { mouse_btn, .. } => (mouse_btn, false),
+            _ => continue,
+        };
+        let Some(mask) = mouse_button_index(button, polled.mouse_button)
+            .checked_sub(1)
+            .and_then(|shift| 1u32.checked_shl(shift.into()))
+        else {
+            continue;
+        };
+        if changed & mask == 0 {
+            changed |= mask;
+            if pressed {
+                held &= !mask;
+            } else {
+                held |= mask;
+            }
+        }
+    }
+    held
+}
+
+/// The raw SDL index of the button of a mouse button event.
+///
+/// `sdl3` only has variants for the first five buttons and reports any other button as
+/// [`SdlMouseButton::Unknown`], so the runner keeps the raw index of those buttons with the event.
+pub fn mouse_button_index(button: SdlMouseButton, raw_index: Option<u8>) -> u8 {
+    match button {
+        SdlMouseButton::Unknown => raw_index.unwrap_or(0),
+        button => button as u8,
+    }
+}
+
+/// Converts a raw SDL button index into a bevy mouse button. Buttons past the forward button
+/// keep their SDL index, e.g. the 6th button is `MouseButton::Other(6)`.
+pub fn convert_sdl_mouse_button(index: u8) -> BevyMouseButton {
+    match i32::from(index) {
+        SDL_BUTTON_LEFT => BevyMouseButton::Left,
+        SDL_BUTTON_MIDDLE => BevyMouseButton::Middle,
+        SDL_BUTTON_RIGHT => BevyMouseButton::Right,
+        SDL_BUTTON_X1 => BevyMouseButton::Back,
+        SDL_BUTTON_X2 => BevyMouseButton::Forward,
+        _ => BevyMouseButton::Other(index.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_ecs::entity::Entity;
+    use bevy_input::mouse::{MouseScrollUnit, MouseWheel};
+    use bevy_math::Vec2;
+    use sdl3::{event::Event as SdlEvent, mouse::MouseButton as SdlMouseButton};
+
+    use super::{SdlMouseWheel, buttons_held_at_focus, convert_sdl_mouse_wheel};
+    use crate::runner::PolledEvent;
+
+    fn wheel(x: f32, y: f32, flipped: bool) -> SdlMouseWheel {
+        let (unit, delta) = convert_sdl_mouse_wheel(x, y);
+        SdlMouseWheel {
+            input: MouseWheel {
+                unit,
+                x: delta.x,
+                y: delta.y,
+                window: Entity::PLACEHOLDER,
+            },
+            position: Vec2::ZERO,
+            mouse: 0,
+            device: None,
+            flipped,
+        }
+    }
+
+    #[test]
+    fn notched_wheels_scroll_lines() {
+        assert_eq!(
+            convert_sdl_mouse_wheel(0.0, 1.0),
+            (MouseScrollUnit::Line, Vec2::new(0.0, 1.0))
+        );
+        assert_eq!(
+            convert_sdl_mouse_wheel(-2.0, -1.0),
+            (MouseScrollUnit::Line, Vec2::new(-2.0, -1.0))
+        );
+    }
+
+    #[test]
+    fn precise_scrolling_scrolls_pixels() {
+        assert_eq!(
+            convert_sdl_mouse_wheel(0.25, -0.5),
+            (MouseScrollUnit::Pixel, Vec2::new(2.5, -5.0))
+        );
+        // a fraction on either axis makes it precise, even with a whole tick on the other
+        assert_eq!(
+            convert_sdl_mouse_wheel(-1.5, 1.0),
+            (MouseScrollUnit::Pixel, Vec2::new(-15.0, 10.0))
+        );
+    }
+
+    #[test]
+    fn wheel_direction() {
+        // scrolling away from the user is positive, the delta is reported as the OS reports it
+        let normal = wheel(1.0, 2.0, false);
+        assert_eq!((normal.input.x, normal.input.y), (1.0, 2.0));
+        assert_eq!(normal.physical_delta(), Vec2::new(1.0, 2.0));
+
+        // with natural scrolling the OS already flipped the delta
+        let flipped = wheel(-1.0, -2.0, true);
+        assert_eq!((flipped.input.x, flipped.input.y), (-1.0, -2.0));
+        assert_eq!(flipped.physical_delta(), Vec2::new(1.0, 2.0));
+
+        // pixels are flipped the same way
+        let precise = wheel(-0.5, 0.0, true);
+        assert_eq!(precise.input.unit, MouseScrollUnit::Pixel);
+        assert_eq!(precise.physical_delta(), Vec2::new(5.0, 0.0));
+    }
+
+    fn button(mouse_btn: SdlMouseButton, index: u8, pressed: bool) -> PolledEvent {
+        let event = if pressed {
+            SdlEvent::MouseButtonDown {
+                timestamp: 0,
+                window_id: 1,
+                which: 1,
+                mouse_btn,
+                clicks: 1,
+                x: 0.0,
+                y: 0.0,
+            }
+        } else {
+            SdlEvent::MouseButtonUp {
+                timestamp: 0,
+                window_id: 1,
+                which: 1,
+                mouse_btn,
+                clicks: 1,
+                x: 0.0,
+                y: 0.0,
+            }
+        };
+        PolledEvent {
+            mouse_button: (mouse_btn == SdlMouseButton::Unknown).then_some(index),
+            ..event.into()
+        }
+    }
+
+    #[test]
+    fn focus_click_is_not_pressed_twice() {
+        // the click that focused the window is already held when the focus is handled
+        let pending = [button(SdlMouseButton::Left, 1, true)];
+        assert_eq!(buttons_held_at_focus(0b1, &pending), 0);
+    }
+
+    #[test]
+    fn buttons_held_at_focus_undo_pending_changes() {
+        let pending = [
+            // held at the focus, released and pressed again after it
+            button(SdlMouseButton::Right, 3, false),
+            button(SdlMouseButton::Right, 3, true),
+            // an extended button pressed after the focus
+            button(SdlMouseButton::Unknown, 8, true),
+            // held at the focus and released after it
+            button(SdlMouseButton::Middle, 2, false),
+        ];
+        // left stays held, the state already has right and button 8 held and middle released
+        let held = 0b1000_0101;
+        assert_eq!(buttons_held_at_focus(held, &pending), 0b0000_0111);
     }
 }
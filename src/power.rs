@@ -0,0 +1,119 @@
+use std::time::{Duration, Instant};
+
+use bevy_ecs::{
+    change_detection::DetectChangesMut,
+    component::Component,
+    resource::Resource,
+    system::{Local, ResMut},
+    world::World,
+};
+use sdl3::sys::power::SDL_PowerState;
+
+use crate::devices::InputDevices;
+
+/// How often [`SystemPower`] is updated. Reading the power state can be slow on some platforms.
+const SYSTEM_POWER_INTERVAL: Duration = Duration::from_secs(5);
+
+/// The state of a battery.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum PowerState {
+    /// The state can't be determined.
+    #[default]
+    Unknown,
+    /// Not plugged in, running on the battery.
+    OnBattery,
+    /// Plugged in, no battery available.
+    NoBattery,
+    /// Plugged in, charging the battery.
+    Charging,
+    /// Plugged in, the battery is charged.
+    Charged,
+}
+
+impl PowerState {
+    fn from_sdl(state: SDL_PowerState) -> Self {
+        match state {
+            SDL_PowerState::ON_BATTERY => PowerState::OnBattery,
+            SDL_PowerState::NO_BATTERY => PowerState::NoBattery,
+            SDL_PowerState::CHARGING => PowerState::Charging,
+            SDL_PowerState::CHARGED => PowerState::Charged,
+            _ => PowerState::Unknown,
+        }
+    }
+}
+
+/// The battery of a gamepad or joystick, updated when SDL reports a change.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Battery {
+    pub state: PowerState,
+    /// The remaining charge in percent, if known.
+    pub percent: Option<u8>,
+}
+
+impl Battery {
+    fn new(state: SDL_PowerState, percent: i32) -> Self {
+        Self {
+            state: PowerState::from_sdl(state),
+            percent: (percent >= 0).then_some(percent.min(100) as u8),
+        }
+    }
+
+    /// Reads the battery of an opened joystick or gamepad.
+    pub fn for_joystick(id: u32) -> Self {
+        let mut percent = -1;
+        // SAFETY: only called on the main thread, SDL checks if the joystick is open
+        let state = unsafe {
+            let joystick = sdl3::sys::joystick::SDL_GetJoystickFromID(id);
+            sdl3::sys::joystick::SDL_GetJoystickPowerInfo(joystick, &mut percent)
+        };
+        Self::new(state, percent)
+    }
+}
+
+/// The power state of the system, e.g. to reduce the frame rate while running on battery.
+/// Updated every few seconds.
+#[derive(Resource, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SystemPower {
+    pub state: PowerState,
+    /// The remaining charge in percent, if known.
+    pub percent: Option<u8>,
+    /// The remaining battery life, if known.
+    pub seconds_left: Option<Duration>,
+}
+
+impl SystemPower {
+    fn load() -> Self {
+        let mut seconds = -1;
+        let mut percent = -1;
+        // SAFETY: can be called from any thread
+        let state = unsafe { sdl3::sys::power::SDL_GetPowerInfo(&mut seconds, &mut percent) };
+        let Battery { state, percent } = Battery::new(state, percent);
+        Self {
+            state,
+            percent,
+            seconds_left: (seconds >= 0).then(|| Duration::from_secs(seconds as u64)),
+        }
+    }
+}
+
+/// system to update [`SystemPower`]
+pub fn update_system_power(
+    mut power: ResMut<SystemPower>,
+    mut last_update: Local<Option<Instant>>,
+) {
+    if last_update.is_some_and(|last_update| last_update.elapsed() < SYSTEM_POWER_INTERVAL) {
+        return;
+    }
+    *last_update = Some(Instant::now());
+    power.set_if_neq(SystemPower::load());
+}
+
+pub fn handle_battery_updated(world: &mut World, which: u32, state: SDL_PowerState, percent: i32) {
+    let devices = world.resource::<InputDevices>();
+    let Some(entity) = devices.gamepad(which).or(devices.joystick(which)) else {
+        return;
+    };
+    if let Some(mut battery) = world.get_mut::<Battery>(entity) {
+        battery.set_if_neq(Battery::new(state, percent));
+    }
+}
@@ -0,0 +1,202 @@
+use std::{ffi::c_void, marker::PhantomData, mem::MaybeUninit, ptr, sync::Arc};
+
+use bevy_app::{App, Plugin};
+use bevy_ecs::{event::Event, resource::Resource, world::World};
+use sdl3::{
+    event::{Event as SdlEvent, EventSender},
+    sys::events::{SDL_Event, SDL_GETEVENT, SDL_PeepEvents},
+};
+
+use crate::{SDL_CONTEXT, SdlContext};
+
+/// Handles the payload of the user events pushed by a [`SdlEventProxy`].
+#[derive(Clone, Copy)]
+pub struct UserEventHandler {
+    /// Decodes the payload and sends it into the world.
+    send: fn(&mut World, *mut c_void),
+    /// Frees the payload of an event that is never sent into the world.
+    free: fn(*mut c_void),
+}
+
+/// Adds a [`SdlEventProxy<T>`] resource that can be used to send `T` events into the app from
+/// other threads.
+///
+/// Needs to be added after [`Sdl3Plugin`](crate::Sdl3Plugin).
+pub struct SdlEventProxyPlugin<T>(PhantomData<T>);
+
+impl<T> Default for SdlEventProxyPlugin<T> {
+    fn default() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<T: Event> Plugin for SdlEventProxyPlugin<T> {
+    fn build(&self, app: &mut App) {
+        let proxy = SDL_CONTEXT
+            .with_borrow_mut(|context| {
+                context
+                    .as_mut()
+                    .expect("Sdl3Plugin needs to be added before SdlEventProxyPlugin")
+                    .register_user_event::<T>()
+            })
+            .unwrap();
+
+        app.add_event::<T>().insert_resource(proxy);
+    }
+}
+
+/// A cloneable handle to push `T` events into the app from any thread.
+///
+/// Events are pushed onto the SDL event queue as user events. The runner sends them into the
+/// world as regular bevy events with the other events of the next frame. Events that are still
+/// queued when the app exits are dropped.
+#[derive(Resource)]
+pub struct SdlEventProxy<T> {
+    event_type: u32,
+    sender: Arc<EventSender>,
+    _marker: PhantomData<fn(T)>,
+}
+
+impl<T> Clone for SdlEventProxy<T> {
+    fn clone(&self) -> Self {
+        Self {
+            event_type: self.event_type,
+            sender: self.sender.clone(),
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<T: Event> SdlEventProxy<T> {
+    /// Push an event onto the SDL event queue.
+    pub fn send_event(&self, event: T) -> Result<(), sdl3::Error> {
+        let data = Box::into_raw(Box::new(event));
+        self.sender
+            .push_event(SdlEvent::User {
+                timestamp: 0,
+                window_id: 0,
+                type_: self.event_type,
+                code: 0,
+                data1: data as *mut c_void,
+                data2: ptr::null_mut(),
+            })
+            .inspect_err(|_| {
+                // SAFETY: the event was never queued, so we still own the payload
+                drop(unsafe { Box::from_raw(data) });
+            })
+    }
+}
+
+impl SdlContext {
+    fn register_user_event<T: Event>(&mut self) -> Result<SdlEventProxy<T>, sdl3::Error> {
+        let event_subsystem = self.sdl.event()?;
+        // SAFETY: the registered type is only pushed by `SdlEventProxy<T>`, which always stores a
+        // `Box<T>` in `data1`.
+        let event_type = unsafe { event_subsystem.register_event()? };
+        self.user_events.insert(
+            event_type,
+            UserEventHandler {
+                send: decode_user_event::<T>,
+                free: free_user_event::<T>,
+            },
+        );
+
+        Ok(SdlEventProxy {
+            event_type,
+            sender: Arc::new(event_subsystem.event_sender()),
+            _marker: PhantomData,
+        })
+    }
+
+    /// Frees the payloads of the proxy events that are still queued, since they are never
+    /// received.
+    pub(crate) fn free_queued_user_events(&self) {
+        for (&event_type, handler) in &self.user_events {
+            let mut event = MaybeUninit::<SDL_Event>::uninit();
+            // SAFETY: only called on the main thread, events of the type are only pushed by a
+            // proxy, so they are user events
+            while unsafe {
+                SDL_PeepEvents(event.as_mut_ptr(), 1, SDL_GETEVENT, event_type, event_type)
+            } > 0
+            {
+                (handler.free)(unsafe { event.assume_init_ref().user.data1 });
+            }
+        }
+    }
+}
+
+fn decode_user_event<T: Event>(world: &mut World, data: *mut c_void) {
+    // SAFETY: `data` was created by `Box::into_raw` in `SdlEventProxy<T>::send_event` and user
+    // events are only decoded once.
+    let event = unsafe { Box::from_raw(data as *mut T) };
+    world.send_event(*event);
+}
+
+fn free_user_event<T: Event>(data: *mut c_void) {
+    // SAFETY: see `decode_user_event`
+    drop(unsafe { Box::from_raw(data as *mut T) });
+}
+
+pub fn handle_user_event(world: &mut World, event_type: u32, data: *mut c_void) {
+    let Some(handler) = SDL_CONTEXT.with_borrow(|context| {
+        context
+            .as_ref()
+            .and_then(|context| context.user_events.get(&event_type).copied())
+    }) else {
+        // user events not pushed by a proxy are still available through `SdlRawEvent`
+        return;
+    };
+
+    (handler.send)(world, data);
+}
+
+/// Frees the payload of a user event that is never handled, e.g. because the app exited before
+/// it was sent into the world.
+pub fn drop_user_event(event_type: u32, data: *mut c_void) {
+    let handler = SDL_CONTEXT.with_borrow(|context| {
+        context
+            .as_ref()
+            .and_then(|context| context.user_events.get(&event_type).copied())
+    });
+    if let Some(handler) = handler {
+        (handler.free)(data);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{sync::Arc, thread};
+
+    use bevy_ecs::event::Event;
+
+    use super::{SdlEventProxy, SdlEventProxyPlugin};
+    use crate::test_app::TestApp;
+
+    /// Counts the payloads that are still alive.
+    #[derive(Event, Debug, Clone)]
+    struct Ping(Arc<()>);
+
+    #[test]
+    fn events_from_other_threads() {
+        let mut app = TestApp::with_plugins(&[], SdlEventProxyPlugin::<Ping>::default());
+        let proxy = app.world().resource::<SdlEventProxy<Ping>>().clone();
+        let payload = Arc::new(());
+
+        let sender = proxy.clone();
+        let ping = Ping(payload.clone());
+        thread::spawn(move || sender.send_event(ping).unwrap())
+            .join()
+            .unwrap();
+        app.update();
+        let events = app.take_events::<Ping>();
+        assert_eq!(events.len(), 1);
+        assert!(Arc::ptr_eq(&events[0].0, &payload));
+        drop(events);
+
+        // events that are still queued when SDL shuts down are freed
+        proxy.send_event(Ping(payload.clone())).unwrap();
+        assert_eq!(Arc::strong_count(&payload), 2);
+        drop(app);
+        assert_eq!(Arc::strong_count(&payload), 1);
+    }
+}
@@ -0,0 +1,34 @@
+use bevy_ecs::{entity::Entity, event::Event, world::World};
+use sdl3::event::Event as SdlEvent;
+
+use crate::{SDL_CONTEXT, SdlContext};
+
+/// An event as it was received from SDL.
+///
+/// Sent for every event pulled off of the SDL event queue, including the ones that are also
+/// translated into bevy events, when [`Sdl3Settings::forward_raw_events`](crate::Sdl3Settings)
+/// is enabled. This allows reacting to SDL events that this crate does not handle yet.
+///
+/// The payload of user events pushed by a [`SdlEventProxy`](crate::SdlEventProxy) is consumed by
+/// the runner, so their `data1` pointer must not be dereferenced.
+#[derive(Event, Debug, Clone)]
+pub struct SdlRawEvent {
+    /// The SDL timestamp of the event in nanoseconds.
+    pub timestamp: u64,
+    /// The window entity the event is associated with, if any.
+    pub window: Option<Entity>,
+    /// The unprocessed SDL event.
+    pub event: SdlEvent,
+}
+
+pub fn send_raw_event(world: &mut World, event: &SdlEvent) {
+    let window = event
+        .get_window_id()
+        .and_then(|window_id| SDL_CONTEXT.with_borrow(SdlContext::get_window_entity(window_id)));
+
+    world.send_event(SdlRawEvent {
+        timestamp: event.get_timestamp(),
+        window,
+        event: event.clone(),
+    });
+}
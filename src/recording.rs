@@ -0,0 +1,925 @@
+use std::{
+    fs::File,
+    io::{self, BufRead, BufReader, BufWriter, Write},
+    path::Path,
+    str::{FromStr, SplitWhitespace},
+};
+
+use bevy_ecs::resource::Resource;
+use sdl3::{
+    event::Event as SdlEvent,
+    gamepad::{Axis, Button},
+    keyboard::{Keycode, Mod, Scancode},
+    mouse::{MouseButton, MouseState, MouseWheelDirection},
+    sys::gamepad::{SDL_GamepadAxis, SDL_GamepadButton},
+};
+
+use tracing::warn;
+
+use crate::{SDL_CONTEXT, devices::sdl_id_list, runner::PolledEvent, timestamp::sdl_ticks_ns};
+
+const HEADER: &str = "bevy_mod_sdl3 input recording v3";
+
+/// Records the input events received by the runner or replays a previous recording.
+///
+/// Events are captured before they are translated into bevy events, so a replay goes through
+/// exactly the same keyboard, mouse and gamepad handling as the original input. While replaying,
+/// input from real devices is ignored.
+///
+/// SDL ids of windows and devices change between runs, so events are recorded with the position
+/// of their window and keyboard, mouse or gamepad in the order the windows were opened and the
+/// devices were connected. Device connections aren't recorded, input is replayed to the device
+/// that was connected in the same position. Events for a window or gamepad that doesn't exist
+/// during the replay are dropped, keyboard and mouse input for a missing device is replayed
+/// without a device.
+#[derive(Resource, Debug, Default)]
+pub enum InputRecorder {
+    #[default]
+    Idle,
+    Recording {
+        /// The SDL timestamp the recording was started at.
+        start: u64,
+        recording: InputRecording,
+    },
+    Replaying {
+        /// The SDL timestamp the replay was started at.
+        start: u64,
+        recording: InputRecording,
+        /// Index of the next event to replay.
+        next: usize,
+    },
+}
+
+impl InputRecorder {
+    /// Start recording input events. Discards any recording or replay in progress.
+    pub fn start_recording(&mut self) {
+        *self = InputRecorder::Recording {
+            start: sdl_ticks_ns(),
+            recording: InputRecording::default(),
+        };
+    }
+
+    /// Stop recording and return the recorded events.
+    pub fn stop_recording(&mut self) -> Option<InputRecording> {
+        match std::mem::take(self) {
+            InputRecorder::Recording { recording, .. } => Some(recording),
+            other => {
+                *self = other;
+                None
+            }
+        }
+    }
+
+    /// Start replaying a recording. Events are replayed with the same timing they were recorded
+    /// with.
+    pub fn replay(&mut self, recording: InputRecording) {
+        *self = InputRecorder::Replaying {
+            start: sdl_ticks_ns(),
+            recording,
+            next: 0,
+        };
+    }
+
+    /// Stop replaying and go back to receiving input from real devices.
+    pub fn stop_replay(&mut self) {
+        if matches!(self, InputRecorder::Replaying { .. }) {
+            *self = InputRecorder::Idle;
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        matches!(self, InputRecorder::Recording { .. })
+    }
+
+    /// Returns true while replaying, until all the events of the recording have been sent.
+    pub fn is_replaying(&self) -> bool {
+        match self {
+            InputRecorder::Replaying {
+                recording, next, ..
+            } => *next < recording.events.len(),
+            _ => false,
+        }
+    }
+
+    /// Record the input events of this frame, or replace them with the events to replay.
+    pub(crate) fn process(&mut self, events: &mut Vec<PolledEvent>) {
+        match self {
+            InputRecorder::Idle => {}
+            InputRecorder::Recording { start, recording } => {
+                let slots = DeviceSlots::current();
+                for polled in events.iter().filter(|polled| is_recordable(&polled.event)) {
+                    let mut event = polled.event.clone();
+                    let window = window_id_mut(&mut event)
+                        .and_then(|window_id| slots.window(std::mem::take(window_id)));
+                    let device = device_id_mut(&mut event).and_then(|(kind, which)| {
+                        let slot = slots.device(kind, *which);
+                        if kind == DeviceKind::Gamepad {
+                            *which = 0;
+                        }
+                        slot
+                    });
+                    recording.events.push(RecordedEvent {
+                        offset: event.get_timestamp().saturating_sub(*start),
+                        event,
+                        window,
+                        device,
+                        extended_mouse_button: polled.mouse_button,
+                    });
+                }
+            }
+            InputRecorder::Replaying {
+                start,
+                recording,
+                next,
+            } => {
+                events.retain(|polled| !is_recordable(&polled.event));
+
+                let slots = DeviceSlots::current();
+                let now = sdl_ticks_ns();
+                while let Some(recorded) = recording.events.get(*next)
+                    && *start + recorded.offset <= now
+                {
+                    *next += 1;
+                    let Some(mut event) = slots.remap(recorded) else {
+                        warn!(
+                            "dropped replayed event without a matching window or gamepad: {:?}",
+                            recorded.event
+                        );
+                        continue;
+                    };
+                    set_timestamp(&mut event, *start + recorded.offset);
+                    events.push(PolledEvent {
+                        event,
+                        mouse_button: recorded.extended_mouse_button,
+                        raw: None,
+                    });
+                }
+            }
+        }
+    }
+}
+
+/// A recorded input event.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RecordedEvent {
+    /// Nanoseconds since the start of the recording.
+    pub offset: u64,
+    /// The recorded event. Its window id and gamepad id are `0`, see [`Self::window`] and
+    /// [`Self::device`]. Keyboard and mouse ids are the ids of the recording run, which are kept
+    /// for input without a connected device, like the ids SDL uses for touch and pen input.
+    pub event: SdlEvent,
+    /// The window the event was sent to, as the position of the window in the order the open
+    /// windows were created. `Some(0)` is usually the primary window.
+    pub window: Option<usize>,
+    /// The keyboard, mouse or gamepad the event came from, as the position of the device in the
+    /// order the connected devices of its kind were connected.
+    pub device: Option<usize>,
+    /// The raw index of the button for mouse button events with a button `sdl3` has no
+    /// [`MouseButton`] variant for.
+    pub extended_mouse_button: Option<u8>,
+}
+
+/// A list of recorded input events that can be saved to and loaded from a file.
+///
+/// The file format is plain text with one event per line.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InputRecording {
+    pub events: Vec<RecordedEvent>,
+}
+
+impl InputRecording {
+    pub fn save(&self, path: impl AsRef<Path>) -> io::Result<()> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        self.write(&mut writer)?;
+        writer.flush()
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> io::Result<Self> {
+        Self::read(BufReader::new(File::open(path)?))
+    }
+
+    pub fn write(&self, mut writer: impl Write) -> io::Result<()> {
+        writeln!(writer, "{HEADER}")?;
+        for RecordedEvent {
+            offset,
+            event,
+            window,
+            device,
+            extended_mouse_button,
+        } in &self.events
+        {
+            let window = Slot(*window);
+            let device = Slot(*device);
+            match event {
+                SdlEvent::KeyDown {
+                    keycode,
+                    scancode,
+                    keymod,
+                    repeat,
+                    which,
+                    raw,
+                    ..
+                }
+                | SdlEvent::KeyUp {
+                    keycode,
+                    scancode,
+                    keymod,
+                    repeat,
+                    which,
+                    raw,
+                    ..
+                } => writeln!(
+                    writer,
+                    "{offset} {} {window} {device} {which} {} {} {} {} {raw}",
+                    if matches!(event, SdlEvent::KeyDown { .. }) {
+                        "key_down"
+                    } else {
+                        "key_up"
+                    },
+                    scancode.map_or(0, Scancode::to_i32),
+                    keycode.map_or(0, Keycode::to_ll),
+                    keymod.bits(),
+                    *repeat as u8,
+                )?,
+                SdlEvent::MouseMotion {
+                    which,
+                    mousestate,
+                    x,
+                    y,
+                    xrel,
+                    yrel,
+                    ..
+                } => writeln!(
+                    writer,
+                    "{offset} mouse_motion {window} {device} {which} {} {x} {y} {xrel} {yrel}",
+                    mousestate.to_sdl_state(),
+                )?,
+                SdlEvent::MouseButtonDown {
+                    which,
+                    mouse_btn,
+                    clicks,
+                    x,
+                    y,
+                    ..
+                }
+                | SdlEvent::MouseButtonUp {
+                    which,
+                    mouse_btn,
+                    clicks,
+                    x,
+                    y,
+                    ..
+                } => writeln!(
+                    writer,
+                    "{offset} {} {window} {device} {which} {} {clicks} {x} {y}",
+                    if matches!(event, SdlEvent::MouseButtonDown { .. }) {
+                        "mouse_button_down"
+                    } else {
+                        "mouse_button_up"
+                    },
+                    extended_mouse_button.unwrap_or(*mouse_btn as u8),
+                )?,
+                SdlEvent::MouseWheel {
+                    which,
+                    x,
+                    y,
+                    direction,
+                    mouse_x,
+                    mouse_y,
+                    ..
+                } => writeln!(
+                    writer,
+                    "{offset} mouse_wheel {window} {device} {which} {x} {y} {} {mouse_x} {mouse_y}",
+                    direction.to_ll(),
+                )?,
+                // gamepad events have no window
+                SdlEvent::ControllerButtonDown { button, .. }
+                | SdlEvent::ControllerButtonUp { button, .. } => writeln!(
+                    writer,
+                    "{offset} {} - {device} {}",
+                    if matches!(event, SdlEvent::ControllerButtonDown { .. }) {
+                        "gamepad_button_down"
+                    } else {
+                        "gamepad_button_up"
+                    },
+                    *button as i32,
+                )?,
+                SdlEvent::ControllerAxisMotion { axis, value, .. } => writeln!(
+                    writer,
+                    "{offset} gamepad_axis - {device} {} {value}",
+                    *axis as i32
+                )?,
+                SdlEvent::ControllerTouchpadDown {
+                    touchpad,
+                    finger,
+                    x,
+                    y,
+                    pressure,
+                    ..
+                }
+                | SdlEvent::ControllerTouchpadMotion {
+                    touchpad,
+                    finger,
+                    x,
+                    y,
+                    pressure,
+                    ..
+                }
+                | SdlEvent::ControllerTouchpadUp {
+                    touchpad,
+                    finger,
+                    x,
+                    y,
+                    pressure,
+                    ..
+                } => writeln!(
+                    writer,
+                    "{offset} {} - {device} {touchpad} {finger} {x} {y} {pressure}",
+                    match event {
+                        SdlEvent::ControllerTouchpadDown { .. } => "gamepad_touchpad_down",
+                        SdlEvent::ControllerTouchpadMotion { .. } => "gamepad_touchpad_motion",
+                        _ => "gamepad_touchpad_up",
+                    },
+                )?,
+                _ => {}
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn read(reader: impl BufRead) -> io::Result<Self> {
+        let mut lines = reader.lines();
+        if lines.next().transpose()?.as_deref() != Some(HEADER) {
+            return Err(invalid_data("not an input recording"));
+        }
+
+        let mut events = Vec::new();
+        for line in lines {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            events.push(
+                parse_event(&line)
+                    .ok_or_else(|| invalid_data(&format!("invalid recorded event: {line}")))?,
+            );
+        }
+
+        Ok(Self { events })
+    }
+}
+
+fn parse_event(line: &str) -> Option<RecordedEvent> {
+    fn next<T: FromStr>(fields: &mut SplitWhitespace) -> Option<T> {
+        fields.next()?.parse().ok()
+    }
+
+    let mut fields = line.split_whitespace();
+    let offset = next(&mut fields)?;
+    let kind: String = next(&mut fields)?;
+    let window = parse_slot(fields.next()?)?;
+    let device = parse_slot(fields.next()?)?;
+    // keyboards and mice are also recorded with their SDL id, gamepads only with their slot
+    let which = if kind.starts_with("gamepad_") {
+        0
+    } else {
+        next(&mut fields)?
+    };
+    // the window id and gamepad id are set when replaying
+    let window_id = 0;
+
+    let mut extended_mouse_button = None;
+    let event = match kind.as_str() {
+        "key_down" | "key_up" => {
+            let scancode = Scancode::from_i32(next(&mut fields)?);
+            let keycode = Keycode::from_i32(next::<u32>(&mut fields)? as i32);
+            let keymod = Mod::from_bits_truncate(next(&mut fields)?);
+            let repeat = next::<u8>(&mut fields)? != 0;
+            let raw = next(&mut fields)?;
+            if kind == "key_down" {
+                SdlEvent::KeyDown {
+                    timestamp: 0,
+                    window_id,
+                    keycode,
+                    scancode,
+                    keymod,
+                    repeat,
+                    which,
+                    raw,
+                }
+            } else {
+                SdlEvent::KeyUp {
+                    timestamp: 0,
+                    window_id,
+                    keycode,
+                    scancode,
+                    keymod,
+                    repeat,
+                    which,
+                    raw,
+                }
+            }
+        }
+        "mouse_motion" => SdlEvent::MouseMotion {
+            timestamp: 0,
+            window_id,
+            which,
+            mousestate: MouseState::from_sdl_state(next(&mut fields)?),
+            x: next(&mut fields)?,
+            y: next(&mut fields)?,
+            xrel: next(&mut fields)?,
+            yrel: next(&mut fields)?,
+        },
+        "mouse_button_down" | "mouse_button_up" => {
+            let index = next(&mut fields)?;
+            let mouse_btn = MouseButton::from_ll(index);
+            if mouse_btn == MouseButton::Unknown {
+                extended_mouse_button = Some(index);
+            }
+            let clicks = next(&mut fields)?;
+            let x = next(&mut fields)?;
+            let y = next(&mut fields)?;
+            if kind == "mouse_button_down" {
+                SdlEvent::MouseButtonDown {
+                    timestamp: 0,
+                    window_id,
+                    which,
+                    mouse_btn,
+                    clicks,
+                    x,
+                    y,
+                }
+            } else {
+                SdlEvent::MouseButtonUp {
+                    timestamp: 0,
+                    window_id,
+                    which,
+                    mouse_btn,
+                    clicks,
+                    x,
+                    y,
+                }
+            }
+        }
+        "mouse_wheel" => SdlEvent::MouseWheel {
+            timestamp: 0,
+            window_id,
+            which,
+            x: next(&mut fields)?,
+            y: next(&mut fields)?,
+            direction: MouseWheelDirection::from_ll(next(&mut fields)?),
+            mouse_x: next(&mut fields)?,
+            mouse_y: next(&mut fields)?,
+        },
+        "gamepad_button_down" | "gamepad_button_up" => {
+            let button = Button::from_ll(SDL_GamepadButton(next(&mut fields)?))?;
+            if kind == "gamepad_button_down" {
+                SdlEvent::ControllerButtonDown {
+                    timestamp: 0,
+                    which,
+                    button,
+                }
+            } else {
+                SdlEvent::ControllerButtonUp {
+                    timestamp: 0,
+                    which,
+                    button,
+                }
+            }
+        }
+        "gamepad_axis" => SdlEvent::ControllerAxisMotion {
+            timestamp: 0,
+            which,
+            axis: Axis::from_ll(SDL_GamepadAxis(next(&mut fields)?))?,
+            value: next(&mut fields)?,
+        },
+        "gamepad_touchpad_down" | "gamepad_touchpad_motion" | "gamepad_touchpad_up" => {
+            let touchpad = next(&mut fields)?;
+            let finger = next(&mut fields)?;
+            let x = next(&mut fields)?;
+            let y = next(&mut fields)?;
+            let pressure = next(&mut fields)?;
+            match kind.as_str() {
+                "gamepad_touchpad_down" => SdlEvent::ControllerTouchpadDown {
+                    timestamp: 0,
+                    which,
+                    touchpad,
+                    finger,
+                    x,
+                    y,
+                    pressure,
+                },
+                "gamepad_touchpad_motion" => SdlEvent::ControllerTouchpadMotion {
+                    timestamp: 0,
+                    which,
+                    touchpad,
+                    finger,
+                    x,
+                    y,
+                    pressure,
+                },
+                _ => SdlEvent::ControllerTouchpadUp {
+                    timestamp: 0,
+                    which,
+                    touchpad,
+                    finger,
+                    x,
+                    y,
+                    pressure,
+                },
+            }
+        }
+        _ => return None,
+    };
+
+    Some(RecordedEvent {
+        offset,
+        event,
+        window,
+        device,
+        extended_mouse_button,
+    })
+}
+
+/// Writes a window or device slot, `-` for none.
+struct Slot(Option<usize>);
+
+impl std::fmt::Display for Slot {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.0 {
+            Some(slot) => write!(f, "{slot}"),
+            None => write!(f, "-"),
+        }
+    }
+}
+
+fn parse_slot(field: &str) -> Option<Option<usize>> {
+    if field == "-" {
+        Some(None)
+    } else {
+        field.parse().ok().map(Some)
+    }
+}
+
+/// The SDL ids of the open windows and connected devices, in the order they were opened and
+/// connected.
+#[derive(Debug, Default)]
+struct DeviceSlots {
+    windows: Vec<u32>,
+    keyboards: Vec<u32>,
+    mice: Vec<u32>,
+    gamepads: Vec<u32>,
+}
+
+impl DeviceSlots {
+    fn current() -> Self {
+        SDL_CONTEXT.with_borrow(|context| {
+            let Some(context) = context.as_ref() else {
+                return Self::default();
+            };
+            // SDL ids only grow, so sorting them gives the order they were created in
+            let mut windows: Vec<u32> = context
+                .windows
+                .winit_to_entity
+                .keys()
+                .map(|id| id.0)
+                .collect();
+            windows.sort_unstable();
+            // SAFETY: only called on the main thread, the lists are freed by `sdl_id_list`
+            let (mut keyboards, mut mice) = unsafe {
+                (
+                    sdl_id_list(sdl3::sys::keyboard::SDL_GetKeyboards),
+                    sdl_id_list(sdl3::sys::mouse::SDL_GetMice),
+                )
+            };
+            keyboards.sort_unstable();
+            mice.sort_unstable();
+            let mut gamepads: Vec<u32> = context.gamepads.keys().copied().collect();
+            gamepads.sort_unstable();
+            Self {
+                windows,
+                keyboards,
+                mice,
+                gamepads,
+            }
+        })
+    }
+
+    fn window(&self, window_id: u32) -> Option<usize> {
+        self.windows.iter().position(|id| *id == window_id)
+    }
+
+    fn devices(&self, kind: DeviceKind) -> &[u32] {
+        match kind {
+            DeviceKind::Keyboard => &self.keyboards,
+            DeviceKind::Mouse => &self.mice,
+            DeviceKind::Gamepad => &self.gamepads,
+        }
+    }
+
+    fn device(&self, kind: DeviceKind, which: u32) -> Option<usize> {
+        self.devices(kind).iter().position(|id| *id == which)
+    }
+
+    /// The recorded event with the ids of the windows and devices of this run, or `None` if its
+    /// window or gamepad doesn't exist.
+    fn remap(&self, recorded: &RecordedEvent) -> Option<SdlEvent> {
+        let mut event = recorded.event.clone();
+        if let Some(window) = recorded.window
+            && let Some(window_id) = window_id_mut(&mut event)
+        {
+            *window_id = *self.windows.get(window)?;
+        }
+        match device_id_mut(&mut event) {
+            Some((DeviceKind::Gamepad, which)) => {
+                *which = *self.gamepads.get(recorded.device?)?;
+            }
+            // keyboard and mouse input doesn't need its device, it is sent without one (id 0) if
+            // the device isn't connected
+            Some((kind, which)) => {
+                if let Some(device) = recorded.device {
+                    *which = self.devices(kind).get(device).copied().unwrap_or(0);
+                }
+            }
+            None => {}
+        }
+        Some(event)
+    }
+}
+
+fn invalid_data(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+/// Whether the event is input from a device that can be recorded and replayed.
+fn is_recordable(event: &SdlEvent) -> bool {
+    matches!(
+        event,
+        SdlEvent::KeyDown { .. }
+            | SdlEvent::KeyUp { .. }
+            | SdlEvent::MouseMotion { .. }
+            | SdlEvent::MouseButtonDown { .. }
+            | SdlEvent::MouseButtonUp { .. }
+            | SdlEvent::MouseWheel { .. }
+            | SdlEvent::ControllerButtonDown { .. }
+            | SdlEvent::ControllerButtonUp { .. }
+            | SdlEvent::ControllerAxisMotion { .. }
+            | SdlEvent::ControllerTouchpadDown { .. }
+            | SdlEvent::ControllerTouchpadMotion { .. }
+            | SdlEvent::ControllerTouchpadUp { .. }
+    )
+}
+
+fn set_timestamp(event: &mut SdlEvent, new_timestamp: u64) {
+    match event {
+        SdlEvent::KeyDown { timestamp, .. }
+        | SdlEvent::KeyUp { timestamp, .. }
+        | SdlEvent::MouseMotion { timestamp, .. }
+        | SdlEvent::MouseButtonDown { timestamp, .. }
+        | SdlEvent::MouseButtonUp { timestamp, .. }
+        | SdlEvent::MouseWheel { timestamp, .. }
+        | SdlEvent::ControllerButtonDown { timestamp, .. }
+        | SdlEvent::ControllerButtonUp { timestamp, .. }
+        | SdlEvent::ControllerAxisMotion { timestamp, .. }
+        | SdlEvent::ControllerTouchpadDown { timestamp, .. }
+        | SdlEvent::ControllerTouchpadMotion { timestamp, .. }
+        | SdlEvent::ControllerTouchpadUp { timestamp, .. } => *timestamp = new_timestamp,
+        _ => {}
+    }
+}
+
+fn window_id_mut(event: &mut SdlEvent) -> Option<&mut u32> {
+    match event {
+        SdlEvent::KeyDown { window_id, .. }
+        | SdlEvent::KeyUp { window_id, .. }
+        | SdlEvent::MouseMotion { window_id, .. }
+        | SdlEvent::MouseButtonDown { window_id, .. }
+        | SdlEvent::MouseButtonUp { window_id, .. }
+        | SdlEvent::MouseWheel { window_id, .. } => Some(window_id),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DeviceKind {
+    Keyboard,
+    Mouse,
+    Gamepad,
+}
+
+fn device_id_mut(event: &mut SdlEvent) -> Option<(DeviceKind, &mut u32)> {
+    match event {
+        SdlEvent::KeyDown { which, .. } | SdlEvent::KeyUp { which, .. } => {
+            Some((DeviceKind::Keyboard, which))
+        }
+        SdlEvent::MouseMotion { which, .. }
+        | SdlEvent::MouseButtonDown { which, .. }
+        | SdlEvent::MouseButtonUp { which, .. }
+        | SdlEvent::MouseWheel { which, .. } => Some((DeviceKind::Mouse, which)),
+        SdlEvent::ControllerButtonDown { which, .. }
+        | SdlEvent::ControllerButtonUp { which, .. }
+        | SdlEvent::ControllerAxisMotion { which, .. }
+        | SdlEvent::ControllerTouchpadDown { which, .. }
+        | SdlEvent::ControllerTouchpadMotion { which, .. }
+        | SdlEvent::ControllerTouchpadUp { which, .. } => Some((DeviceKind::Gamepad, which)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl3::{
+        event::Event as SdlEvent,
+        gamepad::Axis,
+        keyboard::{Keycode, Mod, Scancode},
+        mouse::{MouseButton, MouseState, MouseWheelDirection},
+    };
+
+    use super::{DeviceSlots, InputRecording, RecordedEvent};
+
+    fn recorded(offset: u64, event: SdlEvent, window: Option<usize>) -> RecordedEvent {
+        RecordedEvent {
+            offset,
+            event,
+            window,
+            device: None,
+            extended_mouse_button: None,
+        }
+    }
+
+    fn gamepad_axis(gamepad: usize) -> RecordedEvent {
+        RecordedEvent {
+            offset: 40,
+            event: SdlEvent::ControllerAxisMotion {
+                timestamp: 0,
+                which: 0,
+                axis: Axis::LeftX,
+                value: -1200,
+            },
+            window: None,
+            device: Some(gamepad),
+            extended_mouse_button: None,
+        }
+    }
+
+    #[test]
+    fn round_trip() {
+        let recording = InputRecording {
+            events: vec![
+                RecordedEvent {
+                    device: Some(1),
+                    ..recorded(
+                        0,
+                        SdlEvent::KeyDown {
+                            timestamp: 0,
+                            window_id: 0,
+                            keycode: Some(Keycode::A),
+                            scancode: Some(Scancode::A),
+                            keymod: Mod::LSHIFTMOD,
+                            repeat: false,
+                            which: 7,
+                            raw: 38,
+                        },
+                        Some(0),
+                    )
+                },
+                recorded(
+                    10,
+                    SdlEvent::MouseMotion {
+                        timestamp: 0,
+                        window_id: 0,
+                        which: 2,
+                        mousestate: MouseState::from_sdl_state(1),
+                        x: 10.5,
+                        y: 20.25,
+                        xrel: -0.1,
+                        yrel: 3.0,
+                    },
+                    Some(1),
+                ),
+                // released outside of any window
+                RecordedEvent {
+                    extended_mouse_button: Some(8),
+                    ..recorded(
+                        20,
+                        SdlEvent::MouseButtonUp {
+                            timestamp: 0,
+                            window_id: 0,
+                            which: 2,
+                            mouse_btn: MouseButton::Unknown,
+                            clicks: 1,
+                            x: -4.0,
+                            y: 5.0,
+                        },
+                        None,
+                    )
+                },
+                recorded(
+                    30,
+                    SdlEvent::MouseWheel {
+                        timestamp: 0,
+                        window_id: 0,
+                        which: 2,
+                        x: 0.0,
+                        y: -1.5,
+                        direction: MouseWheelDirection::Flipped,
+                        mouse_x: 1.0,
+                        mouse_y: 2.0,
+                    },
+                    Some(0),
+                ),
+                gamepad_axis(1),
+            ],
+        };
+
+        let mut file = Vec::new();
+        recording.write(&mut file).unwrap();
+        let read = InputRecording::read(file.as_slice()).unwrap();
+        assert_eq!(read, recording);
+    }
+
+    #[test]
+    fn remap_to_current_devices() {
+        let slots = DeviceSlots {
+            windows: vec![5, 9],
+            keyboards: vec![3],
+            mice: vec![1],
+            gamepads: vec![12],
+        };
+
+        let motion = RecordedEvent {
+            device: Some(0),
+            ..recorded(
+                0,
+                SdlEvent::MouseMotion {
+                    timestamp: 0,
+                    window_id: 0,
+                    which: 4,
+                    mousestate: MouseState::from_sdl_state(0),
+                    x: 0.0,
+                    y: 0.0,
+                    xrel: 1.0,
+                    yrel: 1.0,
+                },
+                Some(1),
+            )
+        };
+        assert!(matches!(
+            slots.remap(&motion),
+            Some(SdlEvent::MouseMotion {
+                window_id: 9,
+                which: 1,
+                ..
+            })
+        ));
+        assert!(matches!(
+            slots.remap(&gamepad_axis(0)),
+            Some(SdlEvent::ControllerAxisMotion { which: 12, .. })
+        ));
+
+        let key = |device| RecordedEvent {
+            device,
+            ..recorded(
+                0,
+                SdlEvent::KeyDown {
+                    timestamp: 0,
+                    window_id: 0,
+                    keycode: Some(Keycode::A),
+                    scancode: Some(Scancode::A),
+                    keymod: Mod::NOMOD,
+                    repeat: false,
+                    which: 8,
+                    raw: 38,
+                },
+                Some(0),
+            )
+        };
+        assert!(matches!(
+            slots.remap(&key(Some(0))),
+            Some(SdlEvent::KeyDown {
+                window_id: 5,
+                which: 3,
+                ..
+            })
+        ));
+        // keyboard and mouse input without a device keeps its id, input from a device that isn't
+        // connected is sent without a device
+        assert!(matches!(
+            slots.remap(&key(None)),
+            Some(SdlEvent::KeyDown { which: 8, .. })
+        ));
+        assert!(matches!(
+            slots.remap(&key(Some(1))),
+            Some(SdlEvent::KeyDown { which: 0, .. })
+        ));
+
+        // events for windows and gamepads that don't exist are dropped
+        let missing_window = RecordedEvent {
+            window: Some(2),
+            ..motion
+        };
+        assert_eq!(slots.remap(&missing_window), None);
+        assert_eq!(slots.remap(&gamepad_axis(1)), None);
+    }
+}
@@ -0,0 +1,791 @@
+use std::mem::MaybeUninit;
+
+use bevy_app::{App, AppExit, PluginsState};
+use bevy_ecs::world::World;
+use bevy_input::{ButtonState, touch::TouchPhase};
+use bevy_window::WindowEvent;
+use sdl3::{
+    event::Event as SdlEvent,
+    mouse::MouseButton as SdlMouseButton,
+    sys::events::{
+        SDL_EVENT_GAMEPAD_SENSOR_UPDATE, SDL_EVENT_JOYSTICK_BALL_MOTION,
+        SDL_EVENT_JOYSTICK_BATTERY_UPDATED, SDL_EVENT_KEYBOARD_ADDED, SDL_EVENT_KEYBOARD_REMOVED,
+        SDL_EVENT_KEYMAP_CHANGED, SDL_EVENT_MOUSE_ADDED, SDL_EVENT_MOUSE_REMOVED,
+        SDL_EVENT_SENSOR_UPDATE, SDL_Event, SDL_EventType, SDL_GamepadSensorEvent,
+        SDL_JoyBallEvent, SDL_JoyBatteryEvent, SDL_SensorEvent,
+    },
+};
+
+use crate::{
+    InputRecorder, MouseMotionMode, SDL_CONTEXT, Sdl3Settings,
+    devices::sync_input_devices,
+    gamepad::{
+        handle_gamepad_added, handle_gamepad_axis, handle_gamepad_button, handle_gamepad_removed,
+        handle_gamepad_sensor, handle_gamepad_touchpad,
+    },
+    gamepad_mappings::handle_gamepad_remapped,
+    haptic::sync_haptic_devices,
+    joystick::{
+        handle_joystick_added, handle_joystick_axis, handle_joystick_ball, handle_joystick_button,
+        handle_joystick_hat, handle_joystick_removed,
+    },
+    keyboard::{handle_keyboard_events, handle_keymap_changed, update_keyboard_modifiers},
+    mouse::{handle_mouse_button, handle_mouse_motion, handle_mouse_wheel, mouse_button_index},
+    power::handle_battery_updated,
+    proxy::{drop_user_event, handle_user_event},
+    raw_event::send_raw_event,
+    sensor::handle_sensor_update,
+    window::handle_window_events,
+};
+
+/// Runs the app. Every frame goes through the same steps:
+///
+/// 1. collect all the events that are pending in the SDL event queue
+/// 2. record them or replace them with replayed events, see [`InputRecorder`]
+/// 3. convert them according to [`Sdl3Settings`], i.e. coalescing mouse motion
+/// 4. dispatch them into the world as bevy events in the order they were received
+/// 5. update the resources that mirror SDL state, like
+///    [`KeyboardModifiers`](crate::KeyboardModifiers)
+/// 6. update the app, so the events are available from `PreUpdate` on
+pub fn sdl3_runner(mut app: App) -> AppExit {
+    // events are polled directly, but the pump keeps the event subsystem alive
+    let _event_pump = SDL_CONTEXT
+        .with_borrow_mut(|sdl_context| sdl_context.as_mut().unwrap().sdl.event_pump())
+        .unwrap();
+    let mut events = Vec::new();
+
+    loop {
+        if app.plugins_state() == PluginsState::Ready {
+            app.finish();
+            app.cleanup();
+        }
+
+        poll_events(&mut events);
+
+        // keep the events around until the app is ready to receive them
+        if app.plugins_state() != PluginsState::Cleaned {
+            // wait for more events instead of spinning while plugins finish loading, the event
+            // stays in the queue
+            // SAFETY: only called on the main thread
+            unsafe { sdl3::sys::events::SDL_WaitEventTimeout(std::ptr::null_mut(), 10) };
+            continue;
+        }
+
+        if let Some(exit) = run_frame(&mut app, &mut events) {
+            return exit;
+        }
+    }
+}
+
+/// An event collected from the SDL event queue, with the data `sdl3` drops when converting it.
+#[derive(Clone)]
+pub struct PolledEvent {
+    pub event: SdlEvent,
+    /// The raw index of the button of a mouse button event `sdl3` converts to
+    /// [`SdlMouseButton::Unknown`].
+    pub mouse_button: Option<u8>,
+    /// The raw SDL event, e.g. for the events `sdl3` converts to [`SdlEvent::Unknown`]. `None`
+    /// for replayed events.
+    pub raw: Option<SDL_Event>,
+}
+
+impl From<SdlEvent> for PolledEvent {
+    fn from(event: SdlEvent) -> Self {
+        Self {
+            event,
+            mouse_button: None,
+            raw: None,
+        }
+    }
+}
+
+/// Sends the collected events into the world and updates the app, steps 2 to 6 of
+/// [`sdl3_runner`].
+pub fn run_frame(app: &mut App, events: &mut Vec<PolledEvent>) -> Option<AppExit> {
+    app.world_mut()
+        .resource_mut::<InputRecorder>()
+        .process(events);
+
+    let settings = app.world().resource::<Sdl3Settings>().clone();
+
+    if settings.forward_raw_events {
+        for polled in events.iter() {
+            send_raw_event(app.world_mut(), &polled.event);
+        }
+    }
+
+    if settings.mouse_motion == MouseMotionMode::Coalesced {
+        coalesce_mouse_motion(events);
+    }
+
+    let mut batch = events.drain(..);
+    while let Some(event) = batch.next() {
+        if let Some(exit) = dispatch_event(app.world_mut(), event, batch.as_slice()) {
+            for polled in batch {
+                if let SdlEvent::User { type_, data1, .. } = polled.event {
+                    drop_user_event(type_, data1);
+                }
+            }
+            return Some(exit);
+        }
+    }
+
+    update_keyboard_modifiers(app.world_mut());
+
+    app.update();
+    None
+}
+
+/// Collects the pending events from the SDL event queue.
+///
+/// This does the same as `EventPump::poll_iter`, but keeps the raw event with every event, see
+/// [`PolledEvent`].
+pub fn poll_events(events: &mut Vec<PolledEvent>) {
+    let mut raw = MaybeUninit::uninit();
+    // SAFETY: the runner is on the main thread and SDL initializes the event if one is pending
+    while unsafe { sdl3::sys::events::SDL_PollEvent(raw.as_mut_ptr()) } {
+        let raw = unsafe { raw.assume_init() };
+        let event = SdlEvent::from_ll(raw);
+
+        let mouse_button = match event {
+            SdlEvent::MouseButtonDown {
+                mouse_btn: SdlMouseButton::Unknown,
+                ..
+            }
+            | SdlEvent::MouseButtonUp {
+                mouse_btn: SdlMouseButton::Unknown,
+                ..
+            } => {
+                // SAFETY: the event is a mouse button event
+                Some(unsafe { raw.button.button })
+            }
+            _ => None,
+        };
+
+        events.push(PolledEvent {
+            event,
+            mouse_button,
+            raw: Some(raw),
+        });
+    }
+}
+
+/// Merges consecutive mouse motion events from the same mouse over the same window into a
+/// single event. Events in between, like button presses, keep their position in the queue.
+pub fn coalesce_mouse_motion(events: &mut Vec<PolledEvent>) {
+    let mut coalesced: Vec<PolledEvent> = Vec::with_capacity(events.len());
+
+    for polled in events.drain(..) {
+        if let Some(last) = coalesced.last_mut()
+            && let (
+                SdlEvent::MouseMotion {
+                    timestamp: last_timestamp,
+                    window_id: last_window_id,
+                    which: last_which,
+                    mousestate: last_mousestate,
+                    x: last_x,
+                    y: last_y,
+                    xrel: last_xrel,
+                    yrel: last_yrel,
+                },
+                SdlEvent::MouseMotion {
+                    timestamp,
+                    window_id,
+                    which,
+                    mousestate,
+                    x,
+                    y,
+                    xrel,
+                    yrel,
+                },
+            ) = (&mut last.event, &polled.event)
+            && *last_window_id == *window_id
+            && *last_which == *which
+        {
+            *last_timestamp = *timestamp;
+            *last_mousestate = *mousestate;
+            *last_x = *x;
+            *last_y = *y;
+            *last_xrel += xrel;
+            *last_yrel += yrel;
+            last.raw = polled.raw;
+            continue;
+        }
+
+        coalesced.push(polled);
+    }
+
+    *events = coalesced;
+}
+
+/// Translate a single SDL event into bevy events. Returns the exit code if the app should quit.
+/// `pending` are the events of the batch that are dispatched after this one.
+fn dispatch_event(
+    world: &mut World,
+    polled: PolledEvent,
+    pending: &[PolledEvent],
+) -> Option<AppExit> {
+    let PolledEvent {
+        event,
+        mouse_button,
+        raw,
+    } = polled;
+    // events `sdl3` drops the data of are handled by their raw type, whichever variant `sdl3`
+    // converts them to
+    if let Some(raw) = raw
+        && dispatch_raw_event(world, raw)
+    {
+        return None;
+    }
+    match event {
+        SdlEvent::Window {
+            timestamp,
+            window_id,
+            win_event,
+        } => handle_window_events(world, timestamp, window_id, win_event, pending),
+        // Keyboard Events
+        SdlEvent::KeyDown {
+            timestamp,
+            window_id,
+            keycode,
+            scancode,
+            keymod,
+            repeat,
+            which,
+            raw,
+        } => handle_keyboard_events(
+            world,
+            ButtonState::Pressed,
+            timestamp,
+            window_id,
+            keycode,
+            scancode,
+            keymod,
+            repeat,
+            which,
+            raw,
+        ),
+        SdlEvent::KeyUp {
+            timestamp,
+            window_id,
+            keycode,
+            scancode,
+            keymod,
+            repeat,
+            which,
+            raw,
+        } => handle_keyboard_events(
+            world,
+            ButtonState::Released,
+            timestamp,
+            window_id,
+            keycode,
+            scancode,
+            keymod,
+            repeat,
+            which,
+            raw,
+        ),
+        // Mouse Events
+        SdlEvent::MouseMotion {
+            timestamp,
+            window_id,
+            which,
+            mousestate: _,
+            x,
+            y,
+            xrel,
+            yrel,
+        } => handle_mouse_motion(world, timestamp, window_id, which, x, y, xrel, yrel),
+        SdlEvent::MouseButtonDown {
+            timestamp,
+            window_id,
+            which,
+            mouse_btn,
+            clicks,
+            x,
+            y,
+        } => handle_mouse_button(
+            world,
+            timestamp,
+            window_id,
+            which,
+            mouse_button_index(mouse_btn, mouse_button),
+            ButtonState::Pressed,
+            clicks,
+            x,
+            y,
+        ),
+        SdlEvent::MouseButtonUp {
+            timestamp,
+            window_id,
+            which,
+            mouse_btn,
+            clicks,
+            x,
+            y,
+        } => handle_mouse_button(
+            world,
+            timestamp,
+            window_id,
+            which,
+            mouse_button_index(mouse_btn, mouse_button),
+            ButtonState::Released,
+            clicks,
+            x,
+            y,
+        ),
+        SdlEvent::MouseWheel {
+            timestamp,
+            window_id,
+            which,
+            x,
+            y,
+            direction,
+            // position on window
+            mouse_x,
+            mouse_y,
+        } => handle_mouse_wheel(
+            world, timestamp, window_id, which, x, y, direction, mouse_x, mouse_y,
+        ),
+        SdlEvent::Quit { .. } => {
+            return Some(AppExit::Success);
+        }
+        // TODO: we may need to do more with AppLifecyle to match the winit behavior
+        SdlEvent::AppWillEnterBackground { timestamp: _ } => {
+            world.send_event(WindowEvent::AppLifecycle(
+                bevy_window::AppLifecycle::WillSuspend,
+            ));
+        }
+        SdlEvent::AppDidEnterBackground { timestamp: _ } => {
+            world.send_event(WindowEvent::AppLifecycle(
+                bevy_window::AppLifecycle::Suspended,
+            ));
+        }
+        SdlEvent::AppWillEnterForeground { timestamp: _ } => {
+            world.send_event(WindowEvent::AppLifecycle(
+                bevy_window::AppLifecycle::WillResume,
+            ));
+        }
+        SdlEvent::AppDidEnterForeground { timestamp: _ } => {
+            world.send_event(WindowEvent::AppLifecycle(
+                bevy_window::AppLifecycle::Running,
+            ));
+        }
+        // SdlEvent::AppTerminating { timestamp } => todo!(),
+        // SdlEvent::AppLowMemory { timestamp } => todo!(),
+        // SdlEvent::TextEditing {
+        //     timestamp,
+        //     window_id,
+        //     text,
+        //     start,
+        //     length,
+        // } => todo!(),
+        // SdlEvent::TextInput {
+        //     timestamp,
+        //     window_id,
+        //     text,
+        // } => todo!(),
+        SdlEvent::JoyAxisMotion {
+            timestamp,
+            which,
+            axis_idx,
+            value,
+        } => handle_joystick_axis(world, timestamp, which, axis_idx, value),
+        SdlEvent::JoyHatMotion {
+            timestamp,
+            which,
+            hat_idx,
+            state,
+        } => handle_joystick_hat(world, timestamp, which, hat_idx, state),
+        SdlEvent::JoyButtonDown {
+            timestamp,
+            which,
+            button_idx,
+        } => handle_joystick_button(world, timestamp, which, button_idx, true),
+        SdlEvent::JoyButtonUp {
+            timestamp,
+            which,
+            button_idx,
+        } => handle_joystick_button(world, timestamp, which, button_idx, false),
+        SdlEvent::JoyDeviceAdded { which, .. } => {
+            handle_joystick_added(world, which);
+            sync_haptic_devices(world);
+        }
+        SdlEvent::JoyDeviceRemoved { which, .. } => {
+            handle_joystick_removed(world, which);
+            sync_haptic_devices(world);
+        }
+        // Gamepad Events
+        SdlEvent::ControllerAxisMotion {
+            timestamp,
+            which,
+            axis,
+            value,
+        } => handle_gamepad_axis(world, timestamp, which, axis, value),
+        SdlEvent::ControllerButtonDown {
+            timestamp,
+            which,
+            button,
+        } => handle_gamepad_button(world, timestamp, which, button, true),
+        SdlEvent::ControllerButtonUp {
+            timestamp,
+            which,
+            button,
+        } => handle_gamepad_button(world, timestamp, which, button, false),
+        SdlEvent::ControllerDeviceAdded { timestamp, which } => {
+            handle_gamepad_added(world, timestamp, which)
+        }
+        SdlEvent::ControllerDeviceRemoved { timestamp, which } => {
+            handle_gamepad_removed(world, timestamp, which)
+        }
+        SdlEvent::ControllerDeviceRemapped { timestamp, which } => {
+            handle_gamepad_remapped(world, timestamp, which)
+        }
+        SdlEvent::ControllerTouchpadDown {
+            timestamp,
+            which,
+            touchpad,
+            finger,
+            x,
+            y,
+            pressure,
+        } => handle_gamepad_touchpad(
+            world,
+            timestamp,
+            which,
+            touchpad,
+            finger,
+            TouchPhase::Started,
+            x,
+            y,
+            pressure,
+        ),
+        SdlEvent::ControllerTouchpadMotion {
+            timestamp,
+            which,
+            touchpad,
+            finger,
+            x,
+            y,
+            pressure,
+        } => handle_gamepad_touchpad(
+            world,
+            timestamp,
+            which,
+            touchpad,
+            finger,
+            TouchPhase::Moved,
+            x,
+            y,
+            pressure,
+        ),
+        SdlEvent::ControllerTouchpadUp {
+            timestamp,
+            which,
+            touchpad,
+            finger,
+            x,
+            y,
+            pressure,
+        } => handle_gamepad_touchpad(
+            world,
+            timestamp,
+            which,
+            touchpad,
+            finger,
+            TouchPhase::Ended,
+            x,
+            y,
+            pressure,
+        ),
+        // SdlEvent::FingerDown {
+        //     timestamp,
+        //     touch_id,
+        //     finger_id,
+        //     x,
+        //     y,
+        //     dx,
+        //     dy,
+        //     pressure,
+        // } => todo!(),
+        // SdlEvent::FingerUp {
+        //     timestamp,
+        //     touch_id,
+        //     finger_id,
+        //     x,
+        //     y,
+        //     dx,
+        //     dy,
+        //     pressure,
+        // } => todo!(),
+        // SdlEvent::FingerMotion {
+        //     timestamp,
+        //     touch_id,
+        //     finger_id,
+        //     x,
+        //     y,
+        //     dx,
+        //     dy,
+        //     pressure,
+        // } => todo!(),
+        // SdlEvent::DollarRecord {
+        //     timestamp,
+        //     touch_id,
+        //     gesture_id,
+        //     num_fingers,
+        //     error,
+        //     x,
+        //     y,
+        // } => todo!(),
+        // SdlEvent::MultiGesture {
+        //     timestamp,
+        //     touch_id,
+        //     d_theta,
+        //     d_dist,
+        //     x,
+        //     y,
+        //     num_fingers,
+        // } => todo!(),
+        // SdlEvent::ClipboardUpdate { timestamp } => todo!(),
+        // SdlEvent::DropFile {
+        //     timestamp,
+        //     window_id,
+        //     filename,
+        // } => todo!(),
+        // SdlEvent::DropText {
+        //     timestamp,
+        //     window_id,
+        //     filename,
+        // } => todo!(),
+        // SdlEvent::DropBegin {
+        //     timestamp,
+        //     window_id,
+        // } => todo!(),
+        // SdlEvent::DropComplete {
+        //     timestamp,
+        //     window_id,
+        // } => todo!(),
+        // SdlEvent::AudioDeviceAdded {
+        //     timestamp,
+        //     which,
+        //     iscapture,
+        // } => todo!(),
+        // SdlEvent::AudioDeviceRemoved {
+        //     timestamp,
+        //     which,
+        //     iscapture,
+        // } => todo!(),
+        // SdlEvent::RenderTargetsReset { timestamp } => todo!(),
+        // SdlEvent::RenderDeviceReset { timestamp } => todo!(),
+        SdlEvent::User { type_, data1, .. } => handle_user_event(world, type_, data1),
+        // SdlEvent::Display {
+        //     timestamp,
+        //     display,
+        //     display_event,
+        // } => todo!(),
+        // unhandled events are still available through `SdlRawEvent`
+        _ => {}
+    }
+
+    None
+}
+
+/// Translate an event `sdl3` has no variant for or drops data of, using the raw SDL event.
+/// Returns `false` if the event isn't one of them.
+///
+/// Gamepad sensor updates are only converted to `SdlEvent::ControllerSensorUpdated` with the
+/// `hidapi` feature of `sdl3`, and without their sensor timestamp.
+fn dispatch_raw_event(world: &mut World, raw: SDL_Event) -> bool {
+    // SAFETY: all events start with the event type
+    match SDL_EventType(unsafe { raw.r#type }) {
+        SDL_EVENT_KEYBOARD_ADDED
+        | SDL_EVENT_KEYBOARD_REMOVED
+        | SDL_EVENT_MOUSE_ADDED
+        | SDL_EVENT_MOUSE_REMOVED => sync_input_devices(world),
+        SDL_EVENT_KEYMAP_CHANGED => handle_keymap_changed(world),
+        SDL_EVENT_GAMEPAD_SENSOR_UPDATE => {
+            // SAFETY: the event is a gamepad sensor event
+            let SDL_GamepadSensorEvent {
+                timestamp,
+                which,
+                sensor,
+                data,
+                sensor_timestamp,
+                ..
+            } = unsafe { raw.gsensor };
+            handle_gamepad_sensor(world, timestamp, which, sensor, data, sensor_timestamp);
+        }
+        SDL_EVENT_JOYSTICK_BALL_MOTION => {
+            // SAFETY: the event is a joystick ball event
+            let SDL_JoyBallEvent {
+                timestamp,
+                which,
+                ball,
+                xrel,
+                yrel,
+                ..
+            } = unsafe { raw.jball };
+            handle_joystick_ball(world, timestamp, which, ball, xrel, yrel);
+        }
+        SDL_EVENT_JOYSTICK_BATTERY_UPDATED => {
+            // SAFETY: the event is a joystick battery event
+            let SDL_JoyBatteryEvent {
+                which,
+                state,
+                percent,
+                ..
+            } = unsafe { raw.jbattery };
+            handle_battery_updated(world, which, state, percent);
+        }
+        SDL_EVENT_SENSOR_UPDATE => {
+            // SAFETY: the event is a sensor event
+            let SDL_SensorEvent {
+                timestamp,
+                which,
+                data,
+                sensor_timestamp,
+                ..
+            } = unsafe { raw.sensor };
+            handle_sensor_update(world, timestamp, which, data, sensor_timestamp);
+        }
+        _ => return false,
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use sdl3::{
+        event::Event as SdlEvent,
+        mouse::{MouseButton, MouseState},
+        sys::events::{
+            SDL_EVENT_JOYSTICK_BALL_MOTION, SDL_EVENT_MOUSE_BUTTON_DOWN, SDL_Event,
+            SDL_JoyBallEvent, SDL_MouseButtonEvent,
+        },
+    };
+
+    use super::{PolledEvent, coalesce_mouse_motion, poll_events};
+    use crate::test_app::TestApp;
+
+    fn coalesce(events: Vec<SdlEvent>) -> Vec<SdlEvent> {
+        let mut events = events.into_iter().map(PolledEvent::from).collect();
+        coalesce_mouse_motion(&mut events);
+        events.into_iter().map(|polled| polled.event).collect()
+    }
+
+    fn motion(timestamp: u64, window_id: u32, which: u32, x: f32, xrel: f32) -> SdlEvent {
+        SdlEvent::MouseMotion {
+            timestamp,
+            window_id,
+            which,
+            mousestate: MouseState::from_sdl_state(0),
+            x,
+            y: 0.0,
+            xrel,
+            yrel: 0.0,
+        }
+    }
+
+    fn button_down(timestamp: u64, window_id: u32, which: u32) -> SdlEvent {
+        SdlEvent::MouseButtonDown {
+            timestamp,
+            window_id,
+            which,
+            mouse_btn: MouseButton::Left,
+            clicks: 1,
+            x: 0.0,
+            y: 0.0,
+        }
+    }
+
+    #[test]
+    fn merges_consecutive_motion() {
+        let events = coalesce(vec![
+            motion(1, 1, 1, 1.0, 1.0),
+            motion(2, 1, 1, 3.0, 2.0),
+            motion(3, 1, 1, 6.0, 3.0),
+        ]);
+        // the last position and timestamp with the summed up deltas
+        assert_eq!(events, vec![motion(3, 1, 1, 6.0, 6.0)]);
+    }
+
+    #[test]
+    fn keeps_windows_and_mice_apart() {
+        let events = coalesce(vec![
+            motion(1, 1, 1, 1.0, 1.0),
+            motion(2, 2, 1, 2.0, 1.0),
+            motion(3, 2, 2, 3.0, 1.0),
+            motion(4, 2, 2, 4.0, 1.0),
+        ]);
+        assert_eq!(
+            events,
+            vec![
+                motion(1, 1, 1, 1.0, 1.0),
+                motion(2, 2, 1, 2.0, 1.0),
+                motion(4, 2, 2, 4.0, 2.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn stops_merging_at_other_events() {
+        let events = coalesce(vec![
+            motion(1, 1, 1, 1.0, 1.0),
+            motion(2, 1, 1, 2.0, 1.0),
+            button_down(3, 1, 1),
+            motion(4, 1, 1, 3.0, 1.0),
+            motion(5, 1, 1, 4.0, 1.0),
+        ]);
+        // the press keeps the position it had relative to the motion
+        assert_eq!(
+            events,
+            vec![
+                motion(2, 1, 1, 2.0, 2.0),
+                button_down(3, 1, 1),
+                motion(5, 1, 1, 4.0, 2.0),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_raw_data_with_events() {
+        let mut app = TestApp::new(&[]);
+        app.push_event(SDL_Event {
+            button: SDL_MouseButtonEvent {
+                r#type: SDL_EVENT_MOUSE_BUTTON_DOWN,
+                button: 8,
+                down: true,
+                ..Default::default()
+            },
+        });
+        app.push_event(SDL_Event {
+            jball: SDL_JoyBallEvent {
+                r#type: SDL_EVENT_JOYSTICK_BALL_MOTION,
+                which: 7,
+                xrel: 3,
+                ..Default::default()
+            },
+        });
+
+        let mut events = Vec::new();
+        poll_events(&mut events);
+        let [button, ball] = events.as_slice() else {
+            panic!("expected two events, got {}", events.len());
+        };
+        assert!(matches!(
+            button.event,
+            SdlEvent::MouseButtonDown {
+                mouse_btn: MouseButton::Unknown,
+                ..
+            }
+        ));
+        assert_eq!(button.mouse_button, Some(8));
+        // `sdl3` has no variant for ball motion, the data is only in the raw event
+        assert!(ball.event.is_unknown());
+        assert_eq!(ball.mouse_button, None);
+        // SAFETY: the raw event is a joystick ball event
+        let raw = unsafe { ball.raw.unwrap().jball };
+        assert_eq!((raw.which, raw.xrel), (7, 3));
+    }
+}
@@ -0,0 +1,255 @@
+use bevy_ecs::{
+    component::Component,
+    entity::Entity,
+    event::{Event, EventWriter},
+    name::Name,
+    query::Changed,
+    system::{Commands, Query, ResMut},
+    world::World,
+};
+use bevy_math::Vec3;
+use sdl3::sys::sensor::*;
+use tracing::warn;
+
+use crate::{
+    SDL_CONTEXT,
+    devices::{InputDeviceEvent, InputDevices, sdl_id_list, sdl_name},
+    non_send_marker::NonSendMarker,
+};
+
+/// A motion sensor of the device the app runs on, like the accelerometer of a phone or the gyro
+/// of a Steam Deck. Spawned with a [`Name`], [`SensorType`] and [`SensorEnabled`] when the app
+/// starts.
+///
+/// Readings are sent as [`SensorEvent`]s and kept in [`SensorReading`] while the sensor is
+/// enabled. Sensors of gamepads are separate, see [`GamepadSensors`](crate::GamepadSensors).
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SdlSensor {
+    /// The SDL sensor id of the sensor.
+    pub id: u32,
+}
+
+/// What a [`SdlSensor`] measures.
+///
+/// Accelerometers report acceleration in m/s², including gravity, and gyroscopes report angular
+/// velocity in rad/s. The axes are relative to the device held in its natural orientation: X
+/// points to the right, Y points up and Z points toward the user.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SensorType {
+    Accelerometer,
+    Gyroscope,
+    /// The accelerometer of the left half of a device with detachable halves, like a Joy-Con.
+    AccelerometerLeft,
+    GyroscopeLeft,
+    AccelerometerRight,
+    GyroscopeRight,
+    /// A sensor with a platform specific type.
+    Unknown,
+}
+
+impl SensorType {
+    fn from_sdl(sensor_type: SDL_SensorType) -> Self {
+        match sensor_type {
+            SDL_SensorType::ACCEL => SensorType::Accelerometer,
+            SDL_SensorType::GYRO => SensorType::Gyroscope,
+            SDL_SensorType::ACCEL_L => SensorType::AccelerometerLeft,
+            SDL_SensorType::GYRO_L => SensorType::GyroscopeLeft,
+            SDL_SensorType::ACCEL_R => SensorType::AccelerometerRight,
+            SDL_SensorType::GYRO_R => SensorType::GyroscopeRight,
+            _ => SensorType::Unknown,
+        }
+    }
+}
+
+/// Whether a [`SdlSensor`] sends readings. Sensors are disabled by default, since they send a lot
+/// of events and use power. Change it to enable or disable the sensor.
+#[derive(Component, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SensorEnabled(pub bool);
+
+/// The latest reading of an enabled [`SdlSensor`].
+#[derive(Component, Debug, Clone, Copy, PartialEq, Default)]
+pub struct SensorReading {
+    pub data: Vec3,
+    /// The SDL timestamp of the reading in nanoseconds.
+    pub timestamp: u64,
+    /// The time the sensor took the reading in nanoseconds, if the sensor reports it.
+    pub sensor_timestamp: u64,
+}
+
+/// A reading of an enabled [`SdlSensor`].
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub struct SensorEvent {
+    pub sensor: Entity,
+    pub sensor_type: SensorType,
+    pub data: Vec3,
+    /// The SDL timestamp of the event in nanoseconds.
+    pub timestamp: u64,
+    /// The time the sensor took the reading in nanoseconds, if the sensor reports it. Uses the
+    /// clock of the sensor, so it can only be compared to other readings of the same sensor.
+    pub sensor_timestamp: u64,
+}
+
+/// system to spawn the sensors SDL knows about. SDL doesn't report sensors being added or
+/// removed, so this only runs at startup.
+pub fn spawn_sensors(
+    mut commands: Commands,
+    mut devices: ResMut<InputDevices>,
+    mut device_events: EventWriter<InputDeviceEvent>,
+    // sensors need to be enumerated on the main thread
+    _non_send: NonSendMarker,
+) {
+    let has_sensors = SDL_CONTEXT.with_borrow(|context| {
+        context
+            .as_ref()
+            .is_some_and(|context| context.sensor.is_some())
+    });
+    if !has_sensors {
+        return;
+    }
+    // SAFETY: only called on the main thread, the list is freed by `sdl_id_list`
+    let sensors = unsafe { sdl_id_list(SDL_GetSensors) };
+    for id in sensors {
+        // SAFETY: only called on the main thread, the name is owned by SDL
+        let (name, sensor_type) = unsafe {
+            (
+                sdl_name(SDL_GetSensorNameForID(id)),
+                SensorType::from_sdl(SDL_GetSensorTypeForID(id)),
+            )
+        };
+        let entity = commands
+            .spawn((
+                SdlSensor { id },
+                Name::new(name),
+                sensor_type,
+                SensorEnabled::default(),
+                SensorReading::default(),
+            ))
+            .id();
+        devices.sensors.insert(id, entity);
+        device_events.write(InputDeviceEvent::SensorAdded(entity));
+    }
+}
+
+/// system to open and close sensors when [`SensorEnabled`] changes
+pub fn update_sensors(
+    sensors: Query<(&SdlSensor, &SensorEnabled), Changed<SensorEnabled>>,
+    // sensors need to be opened on the main thread
+    _non_send: NonSendMarker,
+) {
+    SDL_CONTEXT.with_borrow_mut(|context| {
+        let Some(context) = context.as_mut() else {
+            return;
+        };
+        for (&SdlSensor { id }, &SensorEnabled(enabled)) in &sensors {
+            match (enabled, context.sensors.get(&id)) {
+                (true, None) => {
+                    // SAFETY: only called on the main thread, the sensor is checked after opening
+                    let sensor = unsafe { SDL_OpenSensor(id) };
+                    if sensor.is_null() {
+                        warn!("failed to open sensor: {}", sdl3::get_error());
+                        continue;
+                    }
+                    context.sensors.insert(id, sensor);
+                }
+                (false, Some(&sensor)) => {
+                    // SAFETY: the sensor was opened by us
+                    unsafe { SDL_CloseSensor(sensor) };
+                    context.sensors.remove(&id);
+                }
+                _ => {}
+            }
+        }
+    });
+}
+
+pub fn handle_sensor_update(
+    world: &mut World,
+    timestamp: u64,
+    which: u32,
+    data: [f32; 6],
+    sensor_timestamp: u64,
+) {
+    let Some(sensor) = world.resource::<InputDevices>().sensor(which) else {
+        return;
+    };
+    let Some(&sensor_type) = world.get::<SensorType>(sensor) else {
+        return;
+    };
+    let data = Vec3::new(data[0], data[1], data[2]);
+
+    if let Some(mut reading) = world.get_mut::<SensorReading>(sensor) {
+        *reading = SensorReading {
+            data,
+            timestamp,
+            sensor_timestamp,
+        };
+    }
+    world.send_event(SensorEvent {
+        sensor,
+        sensor_type,
+        data,
+        timestamp,
+        sensor_timestamp,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_math::Vec3;
+    use sdl3::sys::events::{SDL_EVENT_SENSOR_UPDATE, SDL_Event, SDL_SensorEvent};
+
+    use super::{SdlSensor, SensorEnabled, SensorEvent, SensorReading, SensorType};
+    use crate::{SDL_CONTEXT, devices::InputDevices, test_app::TestApp};
+
+    #[test]
+    fn sensor_updates_and_failed_open() {
+        // SDL has no virtual sensors, so register an entity for an id SDL doesn't know about
+        let mut app = TestApp::new(&[]);
+        app.update();
+        let sensor = app
+            .world_mut()
+            .spawn((
+                SdlSensor { id: u32::MAX },
+                SensorType::Accelerometer,
+                SensorEnabled::default(),
+                SensorReading::default(),
+            ))
+            .id();
+        app.world_mut()
+            .resource_mut::<InputDevices>()
+            .sensors
+            .insert(u32::MAX, sensor);
+
+        // enabling a sensor that fails to open is only logged
+        app.world_mut()
+            .entity_mut(sensor)
+            .insert(SensorEnabled(true));
+        app.update();
+        SDL_CONTEXT.with_borrow(|context| {
+            assert!(!context.as_ref().unwrap().sensors.contains_key(&u32::MAX));
+        });
+
+        // updates are reported for any sensor with an entity
+        app.push_event(SDL_Event {
+            sensor: SDL_SensorEvent {
+                r#type: SDL_EVENT_SENSOR_UPDATE,
+                reserved: 0,
+                timestamp: 0,
+                which: u32::MAX,
+                data: [0.0, 9.80665, 0.0, 0.0, 0.0, 0.0],
+                sensor_timestamp: 1_000,
+            },
+        });
+        app.update();
+
+        let events = app.take_events::<SensorEvent>();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].sensor, sensor);
+        assert_eq!(events[0].sensor_type, SensorType::Accelerometer);
+        assert_eq!(events[0].data, Vec3::new(0.0, 9.80665, 0.0));
+        assert_eq!(events[0].sensor_timestamp, 1_000);
+        let reading = app.world().get::<SensorReading>(sensor).unwrap();
+        assert_eq!(reading.data, Vec3::new(0.0, 9.80665, 0.0));
+        assert_eq!(reading.timestamp, events[0].timestamp);
+    }
+}
@@ -0,0 +1,142 @@
+//! Runs the plugin in tests, with input from SDL's virtual devices and dummy drivers.
+
+use std::{
+    ffi::CStr,
+    sync::{Mutex, MutexGuard, PoisonError},
+};
+
+use bevy_app::{App, Plugins};
+use bevy_ecs::{
+    event::{Event, Events},
+    world::World,
+};
+use bevy_input::InputPlugin;
+use sdl3::{
+    EventPump,
+    sys::{
+        events::{SDL_Event, SDL_PushEvent},
+        gamepad::{SDL_GAMEPAD_AXIS_COUNT, SDL_GAMEPAD_BUTTON_COUNT},
+        joystick::{
+            SDL_AttachVirtualJoystick, SDL_DetachVirtualJoystick, SDL_JOYSTICK_TYPE_GAMEPAD,
+            SDL_VirtualJoystickDesc,
+        },
+    },
+};
+
+use crate::{
+    SDL_CONTEXT, Sdl3Plugin,
+    runner::{PolledEvent, poll_events, run_frame},
+};
+
+/// SDL and its devices are global, so only one test can use SDL at a time.
+static SDL_LOCK: Mutex<()> = Mutex::new(());
+
+/// An app with the [`Sdl3Plugin`] that is updated frame by frame like the runner does.
+///
+/// SDL is shut down when the app is dropped, which detaches the virtual devices of the test.
+pub struct TestApp {
+    app: App,
+    events: Vec<PolledEvent>,
+    _event_pump: EventPump,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl TestApp {
+    /// Starts SDL with the given hints, e.g. to select a driver.
+    pub fn new(hints: &[(&str, &str)]) -> Self {
+        Self::with_plugins(hints, ())
+    }
+
+    /// Like [`Self::new`], with `plugins` added after the [`Sdl3Plugin`].
+    pub fn with_plugins<M>(hints: &[(&str, &str)], plugins: impl Plugins<M>) -> Self {
+        let lock = SDL_LOCK.lock().unwrap_or_else(PoisonError::into_inner);
+        for (name, value) in hints {
+            sdl3::hint::set(name, value);
+        }
+
+        let mut app = App::new();
+        app.add_plugins((InputPlugin, Sdl3Plugin))
+            .add_plugins(plugins);
+        app.finish();
+        app.cleanup();
+        let event_pump = SDL_CONTEXT
+            .with_borrow_mut(|context| context.as_mut().unwrap().sdl.event_pump())
+            .unwrap();
+
+        Self {
+            app,
+            events: Vec::new(),
+            _event_pump: event_pump,
+            _lock: lock,
+        }
+    }
+
+    /// Runs a frame: sends the pending SDL events into the world and updates the app.
+    pub fn update(&mut self) {
+        poll_events(&mut self.events);
+        run_frame(&mut self.app, &mut self.events);
+    }
+
+    pub fn world(&self) -> &World {
+        self.app.world()
+    }
+
+    pub fn world_mut(&mut self) -> &mut World {
+        self.app.world_mut()
+    }
+
+    /// Attaches a virtual joystick and returns its SDL id. It is connected in the next frame.
+    pub fn attach_virtual_joystick(&mut self, desc: &SDL_VirtualJoystickDesc) -> u32 {
+        // SAFETY: SDL was initialized on this thread, SDL copies the description
+        let id = unsafe { SDL_AttachVirtualJoystick(desc) };
+        assert_ne!(id, 0, "{}", sdl3::get_error());
+        id
+    }
+
+    /// Detaches a virtual joystick. It is disconnected in the next frame.
+    pub fn detach_virtual_joystick(&mut self, id: u32) {
+        // SAFETY: SDL was initialized on this thread
+        assert!(
+            unsafe { SDL_DetachVirtualJoystick(id) },
+            "{}",
+            sdl3::get_error()
+        );
+    }
+
+    /// Adds an event to the SDL event queue, like a device would. It is received in the next
+    /// frame.
+    pub fn push_event(&mut self, mut event: SDL_Event) {
+        // SAFETY: SDL was initialized on this thread, SDL copies the event
+        assert!(
+            unsafe { SDL_PushEvent(&mut event) },
+            "{}",
+            sdl3::get_error()
+        );
+    }
+
+    /// Removes and returns the events of type `E` sent since the last call.
+    pub fn take_events<E: Event>(&mut self) -> Vec<E> {
+        self.world_mut()
+            .resource_mut::<Events<E>>()
+            .drain()
+            .collect()
+    }
+}
+
+/// A virtual joystick with the SDL gamepad layout, which SDL opens as a gamepad without a mapping.
+pub fn virtual_gamepad_desc(name: &'static CStr) -> SDL_VirtualJoystickDesc {
+    SDL_VirtualJoystickDesc {
+        r#type: SDL_JOYSTICK_TYPE_GAMEPAD.0 as u16,
+        naxes: SDL_GAMEPAD_AXIS_COUNT.0 as u16,
+        nbuttons: SDL_GAMEPAD_BUTTON_COUNT.0 as u16,
+        name: name.as_ptr(),
+        ..Default::default()
+    }
+}
+
+impl Drop for TestApp {
+    fn drop(&mut self) {
+        // SDL quits once the event pump is dropped as well
+        SDL_CONTEXT.with_borrow_mut(|context| *context = None);
+    }
+}
@@ -0,0 +1,54 @@
+use std::collections::HashMap;
+
+use bevy_ecs::{event::Event, resource::Resource, world::World};
+
+/// Wraps a bevy input event with the SDL timestamp of the event it was created from.
+///
+/// Sent alongside the wrapped event. Timestamps are in nanoseconds since SDL was initialized and
+/// can be compared against [`sdl_ticks_ns`] to measure how long ago the input happened.
+#[derive(Event, Debug, Clone)]
+pub struct Timestamped<E> {
+    /// The SDL timestamp of the event in nanoseconds.
+    pub timestamp: u64,
+    pub event: E,
+}
+
+/// SDL timestamps of the most recent input received from each device.
+#[derive(Resource, Debug, Default, Clone)]
+pub struct LastInputTimestamps {
+    /// Keyed by the SDL keyboard id.
+    pub keyboards: HashMap<u32, u64>,
+    /// Keyed by the SDL mouse id.
+    pub mice: HashMap<u32, u64>,
+    /// Keyed by the SDL joystick id of the gamepad.
+    pub gamepads: HashMap<u32, u64>,
+}
+
+impl LastInputTimestamps {
+    /// The timestamp of the most recent keyboard input from any keyboard.
+    pub fn keyboard(&self) -> Option<u64> {
+        self.keyboards.values().max().copied()
+    }
+
+    /// The timestamp of the most recent mouse input from any mouse.
+    pub fn mouse(&self) -> Option<u64> {
+        self.mice.values().max().copied()
+    }
+
+    /// The timestamp of the most recent gamepad input from any gamepad.
+    pub fn gamepad(&self) -> Option<u64> {
+        self.gamepads.values().max().copied()
+    }
+}
+
+/// The current SDL time in nanoseconds, using the same clock as event timestamps.
+pub fn sdl_ticks_ns() -> u64 {
+    // SAFETY: only reads the SDL clock
+    unsafe { sdl3::sys::timer::SDL_GetTicksNS() }
+}
+
+/// Send an event followed by its [`Timestamped`] wrapper.
+pub fn send_timestamped<E: Event + Clone>(world: &mut World, timestamp: u64, event: E) {
+    world.send_event(event.clone());
+    world.send_event(Timestamped { timestamp, event });
+}
@@ -0,0 +1,289 @@
+use std::ffi::CString;
+
+use bevy_ecs::{
+    component::Component,
+    entity::Entity,
+    event::{Event, EventReader},
+    query::Added,
+    removal_detection::RemovedComponents,
+    system::Query,
+};
+use bevy_input::gamepad::{GamepadAxis, GamepadButton};
+use sdl3::{
+    gamepad::{Axis as SdlAxis, Button as SdlButton},
+    sys::joystick::{
+        SDL_AttachVirtualJoystick, SDL_DetachVirtualJoystick, SDL_GetJoystickFromID,
+        SDL_JOYSTICK_TYPE_GAMEPAD, SDL_SetJoystickVirtualAxis, SDL_SetJoystickVirtualButton,
+        SDL_VirtualJoystickDesc,
+    },
+};
+use tracing::warn;
+
+use crate::{SDL_CONTEXT, non_send_marker::NonSendMarker};
+
+/// A virtual gamepad, e.g. for tests or an on-screen touch gamepad. Spawn an entity with this
+/// component to attach a virtual gamepad to SDL and remove it or despawn the entity to detach it.
+///
+/// SDL connects the virtual gamepad like real hardware, so it gets its own bevy
+/// [`Gamepad`](bevy_input::gamepad::Gamepad) entity, which can be found with
+/// [`InputDevices::gamepad`](crate::InputDevices::gamepad) and [`VirtualGamepad::id`]. Its input
+/// is set with [`VirtualGamepadInput`] and reported like the input of other gamepads.
+#[derive(Component, Debug, Clone, PartialEq, Eq)]
+pub struct VirtualGamepad {
+    name: String,
+    id: Option<u32>,
+}
+
+impl VirtualGamepad {
+    pub fn new(name: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            id: None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// The SDL joystick id of the gamepad, once it is attached.
+    pub fn id(&self) -> Option<u32> {
+        self.id
+    }
+}
+
+/// Sets the input of a [`VirtualGamepad`]. The input is reported by SDL with the events of the
+/// next frame.
+#[derive(Event, Debug, Clone, Copy, PartialEq)]
+pub enum VirtualGamepadInput {
+    Button {
+        /// The entity with the [`VirtualGamepad`].
+        gamepad: Entity,
+        button: GamepadButton,
+        pressed: bool,
+    },
+    /// Stick axes range from `-1.0` to `1.0`. The triggers can also be set as
+    /// [`GamepadAxis::LeftZ`] and [`GamepadAxis::RightZ`] from `0.0` to `1.0`, to set how far they
+    /// are pressed.
+    Axis {
+        /// The entity with the [`VirtualGamepad`].
+        gamepad: Entity,
+        axis: GamepadAxis,
+        value: f32,
+    },
+}
+
+/// An input of a virtual SDL gamepad.
+enum SdlInput {
+    Button(SdlButton, bool),
+    Axis(SdlAxis, i16),
+}
+
+impl SdlInput {
+    fn from_button(button: GamepadButton, pressed: bool) -> Option<Self> {
+        let button = match button {
+            GamepadButton::South => SdlButton::South,
+            GamepadButton::East => SdlButton::East,
+            GamepadButton::North => SdlButton::North,
+            GamepadButton::West => SdlButton::West,
+            GamepadButton::LeftTrigger => SdlButton::LeftShoulder,
+            GamepadButton::RightTrigger => SdlButton::RightShoulder,
+            GamepadButton::Select => SdlButton::Back,
+            GamepadButton::Start => SdlButton::Start,
+            GamepadButton::Mode => SdlButton::Guide,
+            GamepadButton::LeftThumb => SdlButton::LeftStick,
+            GamepadButton::RightThumb => SdlButton::RightStick,
+            GamepadButton::DPadUp => SdlButton::DPadUp,
+            GamepadButton::DPadDown => SdlButton::DPadDown,
+            GamepadButton::DPadLeft => SdlButton::DPadLeft,
+            GamepadButton::DPadRight => SdlButton::DPadRight,
+            // the triggers are axes in SDL
+            GamepadButton::LeftTrigger2 => {
+                return Some(SdlInput::Axis(SdlAxis::TriggerLeft, trigger_value(pressed)));
+            }
+            GamepadButton::RightTrigger2 => {
+                return Some(SdlInput::Axis(
+                    SdlAxis::TriggerRight,
+                    trigger_value(pressed),
+                ));
+            }
+            GamepadButton::Other(index) => {
+                SdlButton::from_ll(sdl3::sys::gamepad::SDL_GamepadButton(index.into()))?
+            }
+            GamepadButton::C | GamepadButton::Z => return None,
+        };
+        Some(SdlInput::Button(button, pressed))
+    }
+
+    fn from_axis(axis: GamepadAxis, value: f32) -> Option<Self> {
+        let stick = |value: f32| (value.clamp(-1.0, 1.0) * f32::from(i16::MAX)) as i16;
+        let trigger = |value: f32| (value.clamp(0.0, 1.0) * f32::from(i16::MAX)) as i16;
+        // SDL stick axes point down rather than up
+        let (axis, value) = match axis {
+            GamepadAxis::LeftStickX => (SdlAxis::LeftX, stick(value)),
+            GamepadAxis::LeftStickY => (SdlAxis::LeftY, stick(-value)),
+            GamepadAxis::RightStickX => (SdlAxis::RightX, stick(value)),
+            GamepadAxis::RightStickY => (SdlAxis::RightY, stick(-value)),
+            GamepadAxis::LeftZ => (SdlAxis::TriggerLeft, trigger(value)),
+            GamepadAxis::RightZ => (SdlAxis::TriggerRight, trigger(value)),
+            GamepadAxis::Other(_) => return None,
+        };
+        Some(SdlInput::Axis(axis, value))
+    }
+}
+
+fn trigger_value(pressed: bool) -> i16 {
+    if pressed { i16::MAX } else { 0 }
+}
+
+/// system to attach, detach and set the input of [`VirtualGamepad`]s
+pub fn update_virtual_gamepads(
+    mut added: Query<(Entity, &mut VirtualGamepad), Added<VirtualGamepad>>,
+    mut removed: RemovedComponents<VirtualGamepad>,
+    mut inputs: EventReader<VirtualGamepadInput>,
+    // virtual gamepads need to be updated on the main thread
+    _non_send: NonSendMarker,
+) {
+    SDL_CONTEXT.with_borrow_mut(|context| {
+        let Some(context) = context.as_mut() else {
+            return;
+        };
+
+        for entity in removed.read() {
+            if let Some(joystick) = context.virtual_gamepads.remove(&entity) {
+                let id = joystick.id();
+                drop(joystick);
+                // SAFETY: only called on the main thread
+                unsafe { SDL_DetachVirtualJoystick(id) };
+            }
+        }
+
+        for (entity, mut gamepad) in &mut added {
+            let Ok(name) = CString::new(gamepad.name.as_str()) else {
+                warn!(
+                    "virtual gamepad name {:?} contains a nul byte",
+                    gamepad.name
+                );
+                continue;
+            };
+            // an SDL gamepad layout lets SDL map the buttons and axes without a mapping
+            let desc = SDL_VirtualJoystickDesc {
+                r#type: SDL_JOYSTICK_TYPE_GAMEPAD.0 as u16,
+                naxes: sdl3::sys::gamepad::SDL_GAMEPAD_AXIS_COUNT.0 as u16,
+                nbuttons: sdl3::sys::gamepad::SDL_GAMEPAD_BUTTON_COUNT.0 as u16,
+                name: name.as_ptr(),
+                ..Default::default()
+            };
+            // SAFETY: only called on the main thread, SDL copies the name
+            let id = unsafe { SDL_AttachVirtualJoystick(&desc) };
+            if id == 0 {
+                warn!("failed to attach virtual gamepad: {}", sdl3::get_error());
+                continue;
+            }
+            // virtual joysticks need to be opened to set their input
+            match context.joystick.open(id) {
+                Ok(joystick) => {
+                    context.virtual_gamepads.insert(entity, joystick);
+                    gamepad.id = Some(id);
+                }
+                Err(err) => warn!("failed to open virtual gamepad: {err}"),
+            }
+        }
+
+        for input in inputs.read() {
+            let (entity, input) = match *input {
+                VirtualGamepadInput::Button {
+                    gamepad,
+                    button,
+                    pressed,
+                } => (gamepad, SdlInput::from_button(button, pressed)),
+                VirtualGamepadInput::Axis {
+                    gamepad,
+                    axis,
+                    value,
+                } => (gamepad, SdlInput::from_axis(axis, value)),
+            };
+            let Some(input) = input else {
+                continue;
+            };
+            let Some(joystick) = context.virtual_gamepads.get(&entity) else {
+                continue;
+            };
+            // SAFETY: only called on the main thread, the joystick is open
+            unsafe {
+                let joystick = SDL_GetJoystickFromID(joystick.id());
+                match input {
+                    SdlInput::Button(button, pressed) => {
+                        SDL_SetJoystickVirtualButton(joystick, button.to_ll().0, pressed);
+                    }
+                    SdlInput::Axis(axis, value) => {
+                        SDL_SetJoystickVirtualAxis(joystick, axis.to_ll().0, value);
+                    }
+                }
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy_input::gamepad::{Gamepad, GamepadAxis, GamepadButton};
+
+    use super::{VirtualGamepad, VirtualGamepadInput};
+    use crate::{devices::InputDevices, test_app::TestApp};
+
+    #[test]
+    fn virtual_gamepad_input() {
+        let mut app = TestApp::new(&[]);
+        let virtual_gamepad = app
+            .world_mut()
+            .spawn(VirtualGamepad::new("virtual gamepad"))
+            .id();
+        // attached at the end of the frame and connected in the next one
+        app.update();
+        let id = app
+            .world()
+            .get::<VirtualGamepad>(virtual_gamepad)
+            .unwrap()
+            .id()
+            .expect("virtual gamepad was not attached");
+        app.update();
+        let gamepad = app
+            .world()
+            .resource::<InputDevices>()
+            .gamepad(id)
+            .expect("virtual gamepad was not connected");
+        assert!(app.world().get::<Gamepad>(gamepad).is_some());
+
+        app.world_mut().send_event(VirtualGamepadInput::Button {
+            gamepad: virtual_gamepad,
+            button: GamepadButton::South,
+            pressed: true,
+        });
+        app.world_mut().send_event(VirtualGamepadInput::Axis {
+            gamepad: virtual_gamepad,
+            axis: GamepadAxis::LeftStickY,
+            value: 1.0,
+        });
+        app.world_mut().send_event(VirtualGamepadInput::Button {
+            gamepad: virtual_gamepad,
+            button: GamepadButton::RightTrigger2,
+            pressed: true,
+        });
+        // set at the end of the frame and reported by SDL in the next one
+        app.update();
+        app.update();
+        let state = app.world().get::<Gamepad>(gamepad).unwrap();
+        assert!(state.digital().pressed(GamepadButton::South));
+        assert!(state.digital().pressed(GamepadButton::RightTrigger2));
+        assert!(!state.digital().pressed(GamepadButton::East));
+        assert_eq!(state.analog().get(GamepadAxis::LeftStickY), Some(1.0));
+        assert_eq!(state.analog().get(GamepadButton::RightTrigger2), Some(1.0));
+
+        // despawning the entity detaches the gamepad
+        app.world_mut().despawn(virtual_gamepad);
+        app.update();
+        app.update();
+        assert_eq!(app.world().resource::<InputDevices>().gamepad(id), None);
+    }
+}
@@ -4,22 +4,32 @@ use bevy_derive::{Deref, DerefMut};
 use bevy_ecs::{
     entity::{Entity, EntityHashMap},
     error::BevyError,
+    query::Changed,
     system::{Commands, Query},
     world::World,
 };
-use bevy_math::IVec2;
+use bevy_input::keyboard::KeyboardFocusLost;
+use bevy_math::{IVec2, Vec2};
 use bevy_window::{
-    CursorEntered, CursorLeft, RawHandleWrapper, RawHandleWrapperHolder, Window,
+    CursorEntered, CursorGrabMode, CursorLeft, RawHandleWrapper, RawHandleWrapperHolder, Window,
     WindowCloseRequested, WindowFocused, WindowMoved, WindowOccluded, WindowResized, WindowTheme,
     WindowWrapper,
 };
 use raw_window_handle::{
     DisplayHandle, HandleError, HasDisplayHandle, HasWindowHandle, WindowHandle,
 };
-use sdl3::{Sdl, VideoSubsystem, event::WindowEvent, video::Window as Sdl3Window};
+use sdl3::{
+    Sdl, VideoSubsystem, event::WindowEvent, mouse::MouseUtil, video::Window as Sdl3Window,
+};
 use tracing::info;
 
-use crate::{SDL_CONTEXT, SdlContext, non_send_marker::NonSendMarker};
+use crate::{
+    SDL_CONTEXT, SdlContext,
+    keyboard::handle_keyboard_focus_gained,
+    mouse::{release_mouse_buttons, sync_mouse_buttons},
+    non_send_marker::NonSendMarker,
+    runner::PolledEvent,
+};
 
 #[derive(Debug, Hash, PartialEq, Eq, Copy, Clone)]
 pub struct WindowId(pub u32);
@@ -34,8 +44,8 @@ impl From<u32> for WindowId {
 pub struct SyncWindow(Sdl3Window);
 
 // TODO: not sure if this is safe. example only does this for &Sdl3Window. It might be that that is a hack for wgpu.
-unsafe impl<'a> Send for SyncWindow {}
-unsafe impl<'a> Sync for SyncWindow {}
+unsafe impl Send for SyncWindow {}
+unsafe impl Sync for SyncWindow {}
 
 impl HasWindowHandle for SyncWindow {
     fn window_handle(&self) -> Result<WindowHandle<'_>, HandleError> {
@@ -59,6 +69,10 @@ pub struct Sdl3Windows {
     pub entity_to_winit: EntityHashMap<WindowId>,
     /// Maps `sdl3` window identifiers to entities.
     pub winit_to_entity: HashMap<WindowId, Entity>,
+    /// The last cursor position reported by `sdl3` for each window.
+    pub cursor_positions: EntityHashMap<Vec2>,
+    /// Positions the cursor was warped to that `sdl3` hasn't reported back yet.
+    pub pending_warps: EntityHashMap<Vec2>,
     // Many `winit` window functions (e.g. `set_window_icon`) can only be called on the main thread.
     // If they're called on other threads, the program might hang. This marker indicates that this
     // type is not thread-safe and will be `!Send` and `!Sync`.
@@ -71,6 +85,8 @@ impl Sdl3Windows {
             windows: HashMap::default(),
             entity_to_winit: EntityHashMap::new(),
             winit_to_entity: HashMap::new(),
+            cursor_positions: EntityHashMap::new(),
+            pending_warps: EntityHashMap::new(),
             _not_send_sync: std::marker::PhantomData,
         }
     }
@@ -106,6 +122,17 @@ impl Sdl3Windows {
         let id = self.entity_to_winit.get(&entity)?;
         self.windows.get(id)
     }
+
+    /// Warp the cursor inside of a window and remember the position, so the motion event `sdl3`
+    /// sends for the warp isn't reported as cursor movement.
+    pub fn warp_cursor(&mut self, mouse: &MouseUtil, entity: Entity, position: Vec2) {
+        let Some(window) = self.get_window(entity) else {
+            return;
+        };
+        mouse.warp_mouse_in_window(window, position.x, position.y);
+        self.cursor_positions.insert(entity, position);
+        self.pending_warps.insert(entity, position);
+    }
 }
 
 /// system to create the windows when a Window is spawned
@@ -128,7 +155,7 @@ pub fn create_windows(
             info!("Creating new window {} ({})", window.title.as_str(), entity);
             let sdl_window = context
                 .windows
-                .create_window(&context.sdl, entity, &*window)?;
+                .create_window(&context.sdl, entity, &window)?;
 
             if let Some(theme) = match VideoSubsystem::get_system_theme() {
                 sdl3::video::SystemTheme::Unknown => None,
@@ -182,9 +209,10 @@ pub fn create_windows(
 
 pub fn handle_window_events(
     world: &mut World,
-    _timestamp: u64,
+    timestamp: u64,
     window_id: u32,
     event: WindowEvent,
+    pending: &[PolledEvent],
 ) {
     let (window_entity, window_scale) = SDL_CONTEXT
         .with_borrow(SdlContext::get_window_entity_and_scale(window_id))
@@ -252,6 +280,8 @@ pub fn handle_window_events(
             });
         }
         WindowEvent::MouseLeave => {
+            bevy_window.set_physical_cursor_position(None);
+
             world.send_event(CursorLeft {
                 window: window_entity,
             });
@@ -263,6 +293,8 @@ pub fn handle_window_events(
                 window: window_entity,
                 focused: true,
             });
+            handle_keyboard_focus_gained(world, timestamp, window_entity, pending);
+            sync_mouse_buttons(world, timestamp, window_entity, pending);
         }
         WindowEvent::FocusLost => {
             bevy_window.focused = false;
@@ -271,6 +303,10 @@ pub fn handle_window_events(
                 window: window_entity,
                 focused: false,
             });
+            // input that happens while the window isn't focused is never reported, so
+            // release everything that is held
+            world.send_event(KeyboardFocusLost);
+            release_mouse_buttons(world, timestamp, window_entity);
         }
         WindowEvent::CloseRequested => {
             world.send_event(WindowCloseRequested {
@@ -283,11 +319,53 @@ pub fn handle_window_events(
         // WindowEvent::HitTest(_, _) => {}
         // WindowEvent::ICCProfChanged => {}
         // WindowEvent::DisplayChanged(_) => {}
-        e => {
-            dbg!(e);
-        }
+        // unhandled events are still available through `SdlRawEvent`
+        _ => {}
     }
 }
 
-// push changes to bevy window to sdl
-pub fn update_windows() {}
+/// system to push changes made to bevy windows to the sdl windows
+pub fn update_windows(
+    changed_windows: Query<(Entity, &Window), Changed<Window>>,
+    // sdl windows need to be updated on the main thread
+    _non_send: NonSendMarker,
+) {
+    SDL_CONTEXT.with_borrow_mut(|context| {
+        let Some(context) = context.as_mut() else {
+            return;
+        };
+        let mouse = context.sdl.mouse();
+
+        for (entity, window) in &changed_windows {
+            let Some(sdl_window) = context.windows.get_window(entity) else {
+                continue;
+            };
+
+            set_cursor_grab_mode(&mouse, sdl_window, window.cursor_options.grab_mode);
+
+            // the cursor position was changed by a system rather than by sdl
+            if let Some(position) = window.physical_cursor_position()
+                && context.windows.cursor_positions.get(&entity) != Some(&position)
+            {
+                context.windows.warp_cursor(&mouse, entity, position);
+            }
+        }
+    });
+}
+
+fn set_cursor_grab_mode(mouse: &MouseUtil, window: &Sdl3Window, grab_mode: CursorGrabMode) {
+    // A locked cursor uses relative mouse mode, which reports raw motion that keeps coming while
+    // the cursor can't move. System scaling of the motion is disabled in `SdlContext::init`.
+    let relative = grab_mode == CursorGrabMode::Locked;
+    if mouse.relative_mouse_mode(window) != relative {
+        mouse.set_relative_mouse_mode(window, relative);
+    }
+
+    let grabbed = grab_mode != CursorGrabMode::None;
+    // SAFETY: the window is valid as long as we hold a reference to it
+    unsafe {
+        if sdl3::sys::video::SDL_GetWindowMouseGrab(window.raw()) != grabbed {
+            sdl3::sys::video::SDL_SetWindowMouseGrab(window.raw(), grabbed);
+        }
+    }
+}